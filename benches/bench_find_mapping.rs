@@ -56,6 +56,13 @@ fn benchmark_find_mapping(c: &mut Criterion) {
                 assert_pos!(sora: sm.finder().find_mapping(pos).unwrap(), expected);
             })
         });
+        bg.bench_function("sora(lookup_table)", |b| {
+            let sm = sora::SourceMap::from(buf.clone()).unwrap();
+            b.iter(|| {
+                let table = sm.lookup_table();
+                assert_pos!(sora: table.lookup(pos.0, pos.1).unwrap(), expected);
+            })
+        });
         bg.bench_function("sentry", |b| {
             let sm = sentry_sourcemap::SourceMap::from_slice(&buf).unwrap();
 
@@ -94,6 +101,15 @@ fn benchmark_find_mapping(c: &mut Criterion) {
                 }
             })
         });
+        bg.bench_function("sora(lookup_table)", |b| {
+            let sm = sora::SourceMap::from(buf.clone()).unwrap();
+            b.iter(|| {
+                let table = sm.lookup_table();
+                for &(pos, expected) in map_samples {
+                    assert_pos!(sora: table.lookup(pos.0, pos.1).unwrap(), expected);
+                }
+            })
+        });
         bg.bench_function("sentry", |b| {
             let sm = sentry_sourcemap::SourceMap::from_slice(&buf).unwrap();
             b.iter(|| {
@@ -12,10 +12,19 @@ fn sora_parse_borrowed(mut data: Vec<u8>) {
     black_box(sora::BorrowedSourceMap::from_slice(&mut data).unwrap());
 }
 
+#[cfg(feature = "lazy-content")]
+fn sora_parse_lazy_content(mut data: Vec<u8>) {
+    black_box(sora::LazySourceMap::from_slice(&mut data).unwrap());
+}
+
 fn sora_parse_owned(data: Vec<u8>) {
     black_box(sora::SourceMap::from(data).unwrap());
 }
 
+fn sora_into_owned(sm: sora::SourceMap) {
+    black_box(sm.into_owned());
+}
+
 fn sentry_parse(data: Vec<u8>) {
     black_box(sentry_sourcemap::SourceMap::from_slice(&data).unwrap());
 }
@@ -53,9 +62,17 @@ fn benchmark_parse(c: &mut Criterion) {
         bg.bench_with_input("sora(borrowed)", &buf, |b, input| {
             b.iter_batched(|| input.clone(), sora_parse_borrowed, batch_size)
         });
+        #[cfg(feature = "lazy-content")]
+        bg.bench_with_input("sora(borrowed lazy-content)", &buf, |b, input| {
+            b.iter_batched(|| input.clone(), sora_parse_lazy_content, batch_size)
+        });
         bg.bench_with_input("sora(owned)", &buf, |b, input| {
             b.iter_batched(|| input.clone(), sora_parse_owned, batch_size)
         });
+        let owned = sora::SourceMap::from(buf.clone()).unwrap();
+        bg.bench_with_input("sora(owned into_owned)", &owned, |b, input| {
+            b.iter_batched(|| input.clone(), sora_into_owned, batch_size)
+        });
         bg.bench_with_input("sentry", &buf, |b, input| {
             b.iter_batched(|| input.clone(), sentry_parse, batch_size)
         });
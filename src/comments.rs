@@ -0,0 +1,73 @@
+/// Replaces `//` line comments and `/* */` block comments in the JSON with spaces in place,
+/// so that the JSON parser accepts it while borrowing from the buffer still works.
+///
+/// Comment markers inside string literals are left untouched. Only ASCII bytes are written,
+/// so valid UTF-8 stays valid.
+pub(crate) fn strip_comments(json: &mut [u8]) {
+    let len = json.len();
+    let mut idx = 0;
+
+    while idx < len {
+        match json[idx] {
+            b'"' => {
+                idx += 1;
+                while idx < len {
+                    match json[idx] {
+                        b'\\' => idx += 2,
+                        b'"' => break,
+                        _ => idx += 1,
+                    }
+                }
+                idx += 1;
+            }
+            b'/' if idx + 1 < len && json[idx + 1] == b'/' => {
+                while idx < len && json[idx] != b'\n' {
+                    json[idx] = b' ';
+                    idx += 1;
+                }
+            }
+            b'/' if idx + 1 < len && json[idx + 1] == b'*' => {
+                let end = json[idx + 2..]
+                    .windows(2)
+                    .position(|w| w == b"*/")
+                    .map_or(len, |pos| idx + 2 + pos + 2);
+                for byte in &mut json[idx..end] {
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                idx = end;
+            }
+            _ => idx += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip_comments;
+
+    fn strip(json: &str) -> String {
+        let mut buf = json.as_bytes().to_vec();
+        strip_comments(&mut buf);
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(
+            strip("{\n// comment\n\"a\": 1 // 注释\n}"),
+            "{\n          \n\"a\": 1          \n}"
+        );
+        assert_eq!(strip(r#"{/* a */"b":/**/1}"#), r#"{       "b":    1}"#);
+        assert_eq!(strip("/* a\nb */"), "    \n    ");
+        assert_eq!(strip("[1] /* unterminated"), "[1]                ");
+    }
+
+    #[test]
+    fn test_strip_comments_in_string() {
+        let json = r#"{"a": "// not a comment", "b": "/* \" nor this */"}"#;
+        assert_eq!(strip(json), json);
+        assert_eq!(strip(r#"["\\"]//"#), r#"["\\"]  "#);
+    }
+}
@@ -0,0 +1,147 @@
+//! Compatibility with the token interface of the [sourcemap](https://crates.io/crates/sourcemap)
+//! crate, to ease the migration from it.
+//!
+//! ```
+//! # use sora::SourceMap;
+//! let sm = SourceMap::from(
+//!     br#"{"version":3,"sources":["a.js"],"names":["foo"],"mappings":"AAAA,EAACA"}"#.to_vec(),
+//! )
+//! .unwrap();
+//! let token = sm.lookup_token(0, 3).unwrap();
+//! assert_eq!((token.get_src_line(), token.get_src_col()), (0, 1));
+//! assert_eq!(token.get_name(), Some("foo"));
+//! ```
+
+use crate::{BorrowedSourceMap, Mapping};
+use std::fmt::{Display, Formatter};
+
+/// A view of a [Mapping] with its source map, mirroring `sourcemap::Token`.
+///
+/// Like in the `sourcemap` crate, the missing source information is presented as `!0`.
+#[derive(Debug, Clone, Copy)]
+pub struct Token<'m, 'a> {
+    mapping: &'m Mapping,
+    map: &'m BorrowedSourceMap<'a>,
+}
+
+impl<'m, 'a> Token<'m, 'a> {
+    /// Creates a token for a mapping of the source map.
+    #[inline]
+    pub fn new(mapping: &'m Mapping, map: &'m BorrowedSourceMap<'a>) -> Self {
+        Self { mapping, map }
+    }
+
+    /// Returns the underlying mapping.
+    #[inline]
+    pub fn mapping(&self) -> &'m Mapping {
+        self.mapping
+    }
+
+    #[inline]
+    pub fn get_dst_line(&self) -> u32 {
+        self.mapping.generated().line
+    }
+
+    #[inline]
+    pub fn get_dst_col(&self) -> u32 {
+        self.mapping.generated().column
+    }
+
+    #[inline]
+    pub fn get_dst(&self) -> (u32, u32) {
+        (self.get_dst_line(), self.get_dst_col())
+    }
+
+    #[inline]
+    pub fn get_src_line(&self) -> u32 {
+        self.mapping.source_position().map_or(!0, |pos| pos.line)
+    }
+
+    #[inline]
+    pub fn get_src_col(&self) -> u32 {
+        self.mapping.source_position().map_or(!0, |pos| pos.column)
+    }
+
+    #[inline]
+    pub fn get_src(&self) -> (u32, u32) {
+        (self.get_src_line(), self.get_src_col())
+    }
+
+    #[inline]
+    pub fn get_src_id(&self) -> u32 {
+        self.mapping.source_id().unwrap_or(!0)
+    }
+
+    #[inline]
+    pub fn get_source(&self) -> Option<&'m str> {
+        self.mapping
+            .source_id()
+            .and_then(|id| self.map.source_at(id))
+    }
+
+    #[inline]
+    pub fn has_source(&self) -> bool {
+        self.mapping.has_source()
+    }
+
+    #[inline]
+    pub fn get_name_id(&self) -> u32 {
+        self.mapping.name_id().unwrap_or(!0)
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> Option<&'m str> {
+        self.mapping.name_id().and_then(|id| self.map.name_at(id))
+    }
+
+    #[inline]
+    pub fn has_name(&self) -> bool {
+        self.mapping.has_name()
+    }
+}
+
+impl Display for Token<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.get_source().unwrap_or("<unknown>"),
+            self.get_src_line(),
+            self.get_src_col()
+        )
+    }
+}
+
+impl<'a> BorrowedSourceMap<'a> {
+    /// Looks up the closest token to the generated position, like `sourcemap::SourceMap::lookup_token`.
+    ///
+    /// See [find_mapping](Self::find_mapping).
+    pub fn lookup_token(&self, line: u32, col: u32) -> Option<Token<'_, 'a>> {
+        let idx = self
+            .mappings
+            .partition_point(|m| m.generated() <= (line, col).into());
+        idx.checked_sub(1)
+            .map(|idx| Token::new(&self.mappings[idx], self))
+    }
+
+    /// Returns the token at the index of the mappings, like `sourcemap::SourceMap::get_token`.
+    #[inline]
+    pub fn get_token(&self, idx: usize) -> Option<Token<'_, 'a>> {
+        self.mappings
+            .get(idx)
+            .map(|mapping| Token::new(mapping, self))
+    }
+
+    /// Returns the number of tokens, like `sourcemap::SourceMap::get_token_count`.
+    #[inline]
+    pub fn get_token_count(&self) -> u32 {
+        self.mappings.len() as u32
+    }
+
+    /// Iterates over the tokens, like `sourcemap::SourceMap::tokens`.
+    pub fn tokens(&self) -> impl Iterator<Item = Token<'_, 'a>> {
+        self.mappings
+            .iter()
+            .map(move |mapping| Token::new(mapping, self))
+    }
+}
@@ -1,3 +1,5 @@
+use crate::Position;
+use std::collections::TryReserveError;
 use std::error::Error;
 
 pub type ParseResult<T> = Result<T, ParseError>;
@@ -12,6 +14,8 @@ pub enum ParseError {
     Syntax(Box<dyn Error>),
     #[error("a mapping is malformed: \"{0}\"")]
     MappingMalformed(String),
+    #[error("a mapping segment has {found} fields, but only 1, 4 or 5 are allowed")]
+    MappingSegmentFieldCount { found: usize },
     #[error("mappings are unordered")]
     MappingsUnordered,
     #[error("a mapping references unknown source #{0}")]
@@ -27,6 +31,26 @@ pub enum ParseError {
         sources_len: u32,
         sources_content_len: u32,
     },
+    #[error("a generated position exceeds the u32 range")]
+    PositionOverflow,
+    #[error("a VLQ alphabet must consist of 64 distinct ASCII characters other than ',' and ';'")]
+    InvalidAlphabet,
+    #[error("memory allocation failed: {0}")]
+    AllocationFailed(TryReserveError),
+    #[error("source map has more than {limit} mappings")]
+    TooManyMappings { limit: usize },
+    #[error("binary mappings are invalid: {0}")]
+    InvalidBinary(&'static str),
+    #[error("a wrapping array must contain exactly 1 source map, but found {0}")]
+    WrappedArrayLength(usize),
+    #[error("source map has more sources than the u32 id range allows")]
+    TooManySources,
+    #[error("source map has more names than the u32 id range allows")]
+    TooManyNames,
+    #[error("sources entry #{0} is neither a string nor null")]
+    InvalidSource(u32),
+    #[error("sourcesContent entry #{0} is not a valid JSON string")]
+    InvalidSourceContent(u32),
 }
 
 impl From<simd_json::Error> for ParseError {
@@ -35,6 +59,12 @@ impl From<simd_json::Error> for ParseError {
     }
 }
 
+impl From<TryReserveError> for ParseError {
+    fn from(value: TryReserveError) -> Self {
+        Self::AllocationFailed(value)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum ValidateError {
@@ -53,4 +83,81 @@ pub enum ValidateError {
         sources_len: u32,
         sources_content_len: u32,
     },
+    #[error("ignore list references source #{0} which has no content")]
+    IgnoredSourceWithoutContent(u32),
+    #[error("a generated position exceeds the u32 range")]
+    PositionOverflow,
+    #[error(
+        "the mapping at {}:{} has a delta exceeding the 32-bit VLQ range",
+        .0.line,
+        .0.column
+    )]
+    DeltaOverflow(Position),
+    #[error("a relative value moves an id or a source position out of range")]
+    StateOutOfRange,
+}
+
+/// An error from parsing and then validating a source map, see
+/// [from_slice_validated](crate::BorrowedSourceMap::from_slice_validated).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum LoadError {
+    #[error("{0}")]
+    Parse(ParseError),
+    #[error("source map is invalid: {0}")]
+    Validate(ValidateError),
+}
+
+impl From<ParseError> for LoadError {
+    fn from(value: ParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl From<ValidateError> for LoadError {
+    fn from(value: ValidateError) -> Self {
+        Self::Validate(value)
+    }
+}
+
+/// A non-fatal observation about suspicious input that is tolerated during parsing.
+///
+/// See [from_slice_with_warnings](crate::BorrowedSourceMap::from_slice_with_warnings).
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ParseWarning {
+    #[error("ignore list references unknown source #{0}")]
+    IgnoreListOutOfRange(u32),
+    #[error("section at {}:{} has no map and is skipped", .0.line, .0.column)]
+    SectionWithoutMap(Position),
+    #[error("sources entry #{0} is neither a string nor null, and is coerced to null")]
+    InvalidSource(u32),
+    #[error(
+        "source map has {} sources but {} sourcesContent entries, which are resized to match",
+        sources_len,
+        sources_content_len
+    )]
+    MismatchSourcesContent {
+        sources_len: u32,
+        sources_content_len: u32,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ConcatError {
+    #[error(
+        "the first mapping at {}:{} overlaps the last mapping at {}:{}",
+        first.line,
+        first.column,
+        last.line,
+        last.column
+    )]
+    Overlap { last: Position, first: Position },
+    #[error("a generated position exceeds the u32 range")]
+    PositionOverflow,
+    #[error("the source ids exceed the u32 range")]
+    TooManySources,
+    #[error("the name ids exceed the u32 range")]
+    TooManyNames,
 }
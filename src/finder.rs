@@ -1,6 +1,6 @@
 use crate::mapping::{Mapping, Position};
 use crate::mappings::Mappings;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
 
 type FinderState = (
@@ -47,6 +47,138 @@ impl<'a> MappingFinder<'a> {
     }
 }
 
+/// `CachingFinder` is a helper struct for finding mappings within a [BorrowedSourceMap](crate::BorrowedSourceMap),
+/// which memoizes the range of mappings of recently queried generated lines.
+///
+/// It suits scenarios like UIs, where the same few lines are queried repeatedly but not
+/// necessarily in order: repeated queries on a cached line skip the search over the whole mappings.
+///
+/// The cache is a small LRU holding at most `capacity` lines, which costs 16 bytes per line
+/// on 64-bit platforms. It uses interior mutability, so the finder is not `Sync`.
+#[derive(Debug)]
+pub struct CachingFinder<'a> {
+    mappings: &'a Mappings,
+    capacity: usize,
+    // (generated line, start index, end index), the most recently used one is the last
+    cache: RefCell<Vec<(u32, usize, usize)>>,
+}
+
+impl<'a> CachingFinder<'a> {
+    pub(crate) fn new(mappings: &'a Mappings, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            mappings,
+            capacity,
+            cache: RefCell::new(Vec::with_capacity(capacity)),
+        }
+    }
+
+    /// Finds the mapping for a given generated position.
+    ///
+    /// If an exact match is not found, this method returns the closest preceding mapping.
+    /// If there are no preceding mappings, it returns `None`.
+    pub fn find_mapping<P>(&self, pos: P) -> Option<Mapping>
+    where
+        P: Into<Position>,
+    {
+        let pos = pos.into();
+        let (start, end) = self.line_bounds(pos.line);
+        // SAFETY: the bounds are calculated within mappings
+        let line_mappings = unsafe { self.mappings.get_unchecked(start..end) };
+        let idx = match line_mappings.binary_search_by_key(&pos.column, |m| m.generated().column) {
+            Ok(idx) => start + idx,
+            // the closest preceding mapping is on a previous line
+            Err(0) if start == 0 => return None,
+            Err(idx) => start + idx - 1,
+        };
+        // SAFETY: idx is guaranteed valid
+        Some(unsafe { self.mappings.get_unchecked(idx) }.clone())
+    }
+
+    fn line_bounds(&self, line: u32) -> (usize, usize) {
+        let mut cache = self.cache.borrow_mut();
+        if let Some(idx) = cache.iter().rposition(|&(l, ..)| l == line) {
+            let entry = cache.remove(idx);
+            cache.push(entry);
+            return (entry.1, entry.2);
+        }
+
+        let range = self.mappings.lines_range(line, line);
+        let (start, end) = (range.start, range.end);
+
+        if cache.len() == self.capacity {
+            cache.remove(0);
+        }
+        cache.push((line, start, end));
+        (start, end)
+    }
+}
+
+/// `LookupTable` is a precomputed table for finding mappings by generated position,
+/// similar to `generate_lookup_table` of `oxc_sourcemap`.
+///
+/// It owns a copy of the mappings grouped by generated line, so it is independent of the source
+/// map it was created from. Each finding is a binary search over the mapped lines followed by
+/// a binary search on the columns of that line.
+///
+/// Only the lines with mappings are stored, so the size of the table doesn't depend on the
+/// number of generated lines, which can be huge with the offsets of an index map.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LookupTable {
+    mappings: Vec<Mapping>,
+    // (generated line, index of its first mapping) of each line with mappings, the mappings
+    // of a line end at the first mapping of the next entry
+    line_offsets: Vec<(u32, usize)>,
+}
+
+impl LookupTable {
+    pub(crate) fn new(mappings: &Mappings) -> Self {
+        let mut line_offsets = Vec::<(u32, usize)>::new();
+        for (idx, mapping) in mappings.iter().enumerate() {
+            let line = mapping.generated().line;
+            if line_offsets.last().map(|&(last, _)| last) != Some(line) {
+                line_offsets.push((line, idx));
+            }
+        }
+
+        Self {
+            mappings: mappings.to_vec(),
+            line_offsets,
+        }
+    }
+
+    /// Finds the mapping for a given generated position.
+    ///
+    /// If an exact match is not found, this method returns the closest preceding mapping.
+    /// If there are no preceding mappings, it returns `None`.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<Mapping> {
+        let line_start = |idx: usize| {
+            self.line_offsets
+                .get(idx)
+                .map_or(self.mappings.len(), |&(_, start)| start)
+        };
+        let idx = match self
+            .line_offsets
+            .binary_search_by_key(&line, |&(line, _)| line)
+        {
+            Ok(idx) => {
+                let (start, end) = (line_start(idx), line_start(idx + 1));
+                match self.mappings[start..end]
+                    .binary_search_by_key(&column, |m| m.generated().column)
+                {
+                    Ok(idx) => start + idx,
+                    // the closest preceding mapping may be on a previous line
+                    Err(idx) => (start + idx).checked_sub(1)?,
+                }
+            }
+            // the line has no mappings, so the closest preceding mapping is the last one
+            // before the next mapped line
+            Err(idx) => line_start(idx).checked_sub(1)?,
+        };
+        Some(self.mappings[idx].clone())
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct MappingFinderImpl<'a> {
     mappings: &'a Mappings,
@@ -81,8 +213,11 @@ impl<'a> MappingFinderImpl<'a> {
                         // this is the branch that initial state will enter
                         self.find_by_binary_search_up_to(pos, last_idx)
                     }
-                } else {
+                } else if last_idx < self.mappings.len() {
                     Some(last_idx)
+                } else {
+                    // the initial state with the max position has no valid index
+                    self.find_by_binary_search_up_to(pos, last_idx)
                 }
                 .map(|idx| {
                     // SAFETY: idx returned is guaranteed valid
@@ -98,7 +233,7 @@ impl<'a> MappingFinderImpl<'a> {
     }
 
     fn find_by_linear_search_up_to(&self, pos: Position, max_idx: usize) -> Option<usize> {
-        (0..max_idx).rev().position(|idx| {
+        (0..max_idx).rev().find(|&idx| {
             // SAFETY: idx from 0 to max_idx is obviously safe since the max_idx is calculated
             //   within mappings before
             unsafe { self.mappings.get_unchecked(idx) }
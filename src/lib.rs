@@ -41,18 +41,38 @@
 //!
 //! ## Features
 //!
-//! - `builder`: Enables [SourceMapBuilder] and functions like [Mappings::new] for manual construction of source maps.
+//! - `builder`: Enables [SourceMapBuilder], [MappingsBuilder] and functions like [Mappings::new] for manual construction
+//!   of source maps.
+//! - `compat-sourcemap`: Enables the [compat] module mirroring the token interface of the `sourcemap` crate.
+//! - `dedup`: Enables [BorrowedSourceMap::dedup_names_and_sources] to deduplicate repeated names
+//!   and sources within a map.
+//! - `digest`: Enables [BorrowedSourceMap::content_digest] to compute the SHA-256 digest of a
+//!   source content.
 //! - `index-map`: Enables support for index maps, as specified in [spec](https://tc39.es/source-map/#index-map).
 //! - `ignore_list`: Enables support for [ignoreList](https://tc39.es/source-map/#ignorelist).
+//! - `json-comments`: Allows `//` and `/* */` comments in the JSON when parsing, which are found in
+//!   hand-edited source maps.
+//! - `lazy-content`: Enables [LazySourceMap], which only unescapes the `sourcesContent` entries on
+//!   first access.
+//! - `metrics`: Enables [BorrowedSourceMap::from_slice_with_metrics] to measure the parsing.
+//! - `serde`: Enables [SourceMap::from_json_value] to parse a source map from a `serde_json::Value`.
+//! - `testing`: Enables the [testing] module with assertions for tests of dependent crates.
 //!
 
+#[cfg(feature = "json-comments")]
+mod comments;
+#[cfg(feature = "compat-sourcemap")]
+pub mod compat;
 mod error;
 mod finder;
 mod hint;
 mod mapping;
+pub mod mapping_builder;
 mod mappings;
 mod sourcemap;
 mod splitter;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod vlq;
 
 pub use error::*;
@@ -60,3 +80,5 @@ pub use finder::*;
 pub use mapping::*;
 pub use mappings::*;
 pub use sourcemap::*;
+pub use splitter::MappingSplitter;
+pub use vlq::VlqAlphabet;
@@ -1,4 +1,9 @@
+use crate::vlq::VlqEncoder;
+use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
+use std::io;
+use std::io::Write;
+use std::ops::{Add, Sub};
 
 /// `Position` represents a zero-based line and zero-based column in a file.
 ///
@@ -33,6 +38,92 @@ impl Position {
     pub const fn new(line: u32, column: u32) -> Self {
         Self { line, column }
     }
+
+    /// Adds `n` to the line, returning `None` if overflow occurred.
+    #[inline]
+    pub const fn checked_add_line(self, n: u32) -> Option<Self> {
+        match self.line.checked_add(n) {
+            Some(line) => Some(Self::new(line, self.column)),
+            None => None,
+        }
+    }
+
+    /// Adds `n` to the column, returning `None` if overflow occurred.
+    #[inline]
+    pub const fn checked_add_column(self, n: u32) -> Option<Self> {
+        match self.column.checked_add(n) {
+            Some(column) => Some(Self::new(self.line, column)),
+            None => None,
+        }
+    }
+
+    /// Adds `n` to the line, saturating at `u32::MAX`.
+    #[inline]
+    pub const fn saturating_add_line(self, n: u32) -> Self {
+        Self::new(self.line.saturating_add(n), self.column)
+    }
+
+    /// Adds `n` to the column, saturating at `u32::MAX`.
+    #[inline]
+    pub const fn saturating_add_column(self, n: u32) -> Self {
+        Self::new(self.line, self.column.saturating_add(n))
+    }
+
+    /// Adds `(lines, columns)` component-wise, returning `None` if overflow occurred.
+    #[inline]
+    pub const fn checked_add(self, (lines, columns): (u32, u32)) -> Option<Self> {
+        match (
+            self.line.checked_add(lines),
+            self.column.checked_add(columns),
+        ) {
+            (Some(line), Some(column)) => Some(Self::new(line, column)),
+            _ => None,
+        }
+    }
+
+    /// Subtracts `(lines, columns)` component-wise, returning `None` if underflow occurred.
+    #[inline]
+    pub const fn checked_sub(self, (lines, columns): (u32, u32)) -> Option<Self> {
+        match (
+            self.line.checked_sub(lines),
+            self.column.checked_sub(columns),
+        ) {
+            (Some(line), Some(column)) => Some(Self::new(line, column)),
+            _ => None,
+        }
+    }
+}
+
+/// Adds `(lines, columns)` component-wise, saturating at the numeric bounds.
+///
+/// Unlike advancing through text, adding lines doesn't reset the column, so
+/// `Position::new(1, 5) + (1, 2)` is `Position::new(2, 7)`. This matches shifting a whole region,
+/// e.g. when the generated code is moved.
+impl Add<(u32, u32)> for Position {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, (lines, columns): (u32, u32)) -> Self {
+        Self::new(
+            self.line.saturating_add(lines),
+            self.column.saturating_add(columns),
+        )
+    }
+}
+
+/// Subtracts `(lines, columns)` component-wise, saturating at the numeric bounds.
+///
+/// See the [Add] implementation for the semantics.
+impl Sub<(u32, u32)> for Position {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, (lines, columns): (u32, u32)) -> Self {
+        Self::new(
+            self.line.saturating_sub(lines),
+            self.column.saturating_sub(columns),
+        )
+    }
 }
 
 impl From<(u32, u32)> for Position {
@@ -42,7 +133,7 @@ impl From<(u32, u32)> for Position {
 }
 
 /// Presents a specific position in a specific source file.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 pub struct SourceInfo {
     pub id: u32,
     pub position: Position,
@@ -88,6 +179,10 @@ impl<const N: usize> OptionNum<N> {
 /// Presents an item of the `mappings`.
 ///
 /// Lines and columns in `Mapping` are start at 0. See [Position].
+///
+/// Mappings are ordered by generated position, then source information, then name id,
+/// where a missing source or name comes first. [Mappings::sort](crate::Mappings::sort)
+/// uses this order.
 #[derive(Clone, Eq, PartialEq)]
 pub struct Mapping {
     generated: Position,
@@ -116,6 +211,21 @@ impl Debug for Mapping {
     }
 }
 
+impl Ord for Mapping {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.generated
+            .cmp(&other.generated)
+            .then_with(|| self.source_info().cmp(&other.source_info()))
+            .then_with(|| self.name_id().cmp(&other.name_id()))
+    }
+}
+
+impl PartialOrd for Mapping {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Mapping {
     #[inline(always)]
     pub const fn new(generated_line: u32, generated_col: u32) -> Self {
@@ -147,12 +257,53 @@ impl Mapping {
 }
 
 impl Mapping {
+    /// Returns the fields as `[generated_line, generated_col, source_id, source_line,
+    /// source_col, name_id]`, where missing values are `u32::MAX`.
+    #[inline]
+    pub(crate) fn to_packed(&self) -> [u32; 6] {
+        let [source_id, source_line, source_col] = self.source.0;
+        let [name_id] = self.name.0;
+        [
+            self.generated.line,
+            self.generated.column,
+            source_id,
+            source_line,
+            source_col,
+            name_id,
+        ]
+    }
+
+    /// The reverse of [to_packed](Self::to_packed), which returns `None` if the name is present
+    /// without the source.
+    #[inline]
+    pub(crate) fn from_packed(packed: [u32; 6]) -> Option<Self> {
+        let [generated_line, generated_col, source_id, source_line, source_col, name_id] = packed;
+        let mapping = Self {
+            generated: Position::new(generated_line, generated_col),
+            source: OptionNum::new([source_id, source_line, source_col]),
+            name: OptionNum::new([name_id]),
+        };
+        if mapping.source.is_missing() {
+            if !mapping.name.is_missing() {
+                return None;
+            }
+            return Some(Self::new(generated_line, generated_col));
+        }
+        Some(mapping)
+    }
+
     /// Returns the generated position of the mapping.
     #[inline]
     pub fn generated(&self) -> Position {
         self.generated
     }
 
+    /// Checks if the mapping is on the same generated line as the other one.
+    #[inline]
+    pub fn same_generated_line(&self, other: &Mapping) -> bool {
+        self.generated.line == other.generated.line
+    }
+
     /// Returns the source id if available.
     #[inline]
     pub fn source_id(&self) -> Option<u32> {
@@ -181,6 +332,27 @@ impl Mapping {
         !self.source.is_missing()
     }
 
+    /// Checks if the mapping has no source information, i.e. `!has_source()`.
+    ///
+    /// Besides the 1-length segments of `mappings`, such mappings include the end sentinel,
+    /// which the decoder appends at the start of the last generated line when `mappings` ends
+    /// with empty lines (see [insert_end_sentinel](crate::ParseOptions::insert_end_sentinel)).
+    /// Tools round-tripping maps can filter them out with this predicate, on a best-effort
+    /// basis, since a sentinel looks the same as a segment of `mappings`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;"}"#.to_vec()).unwrap();
+    /// let sentinel = sm.mappings().last().unwrap();
+    /// assert!(sentinel.is_source_less());
+    /// assert_eq!((sentinel.generated().line, sentinel.generated().column), (1, 0));
+    /// ```
+    #[inline]
+    pub fn is_source_less(&self) -> bool {
+        !self.has_source()
+    }
+
     /// Returns the name information if available.
     ///
     /// Note that in a mapping,
@@ -195,4 +367,324 @@ impl Mapping {
     pub fn has_name(&self) -> bool {
         !self.name.is_missing()
     }
+
+    /// Returns the fields in the shape of a decoded segment of `mappings`, i.e.
+    /// `[generated column, source id, source line, source column, name id]` truncated to the
+    /// present fields, which is convenient for logging and for tools expecting that shape.
+    ///
+    /// Note that the values are absolute, while the ones of a segment are relative to the
+    /// previous segment, so they only equal for the first segment of `mappings`. The generated
+    /// line is implied by the position of a segment, and isn't included.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::Mapping;
+    /// assert_eq!(Mapping::new(0, 4).as_array(), vec![4]);
+    /// assert_eq!(Mapping::new(0, 4).with_source(1, 2, 3).with_name(5).as_array(), vec![4, 1, 2, 3, 5]);
+    /// ```
+    pub fn as_array(&self) -> Vec<i64> {
+        let mut fields = vec![self.generated().column as i64];
+        if let Some(source_info) = self.source_info() {
+            fields.extend([
+                source_info.id as i64,
+                source_info.position.line as i64,
+                source_info.position.column as i64,
+            ]);
+        }
+        if let Some(name_id) = self.name_id() {
+            fields.push(name_id as i64);
+        }
+        fields
+    }
+}
+
+/// `EncoderState` is the running state of encoding [Mapping] entries one at a time into the
+/// `mappings` field, see [Mapping::encode_relative].
+///
+/// The VLQ encoded values of a segment are relative to the previous segment:
+/// - the generated column is relative to the previous segment on the same generated line, and
+///   starts over from `0` on each line,
+/// - the source id, the source position and the name id are relative to the last segment which
+///   has them, regardless of the generated line, and start from `0` for the whole field.
+///
+/// A new state is at the start of the `mappings` field, before the first segment.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EncoderState {
+    started: bool,
+    generated: Position,
+    source_id: u32,
+    source_position: Position,
+    name_id: u32,
+}
+
+impl Default for EncoderState {
+    fn default() -> Self {
+        Self {
+            started: false,
+            generated: Position::new(0, 0),
+            source_id: 0,
+            source_position: Position::new(0, 0),
+            name_id: 0,
+        }
+    }
+}
+
+impl EncoderState {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the generated position of the last encoded mapping, or `0:0` if none.
+    #[inline]
+    pub fn generated(&self) -> Position {
+        self.generated
+    }
+}
+
+impl Mapping {
+    /// Encodes the mapping as a segment of the `mappings` field into the writer, relative to the
+    /// [state](EncoderState), which is then updated to continue with the next mapping.
+    ///
+    /// The separators before the segment are written as well: a `;` for each generated line
+    /// moved forward, or a `,` after a previous segment on the same line. Encoding the mappings
+    /// of [Mappings](crate::Mappings) in order with a new state results in
+    /// [to_encoded_string](crate::Mappings::to_encoded_string).
+    ///
+    /// Fails with [InvalidInput](io::ErrorKind::InvalidInput) if the mapping is on a generated
+    /// line before the one of the last encoded mapping, since lines can't go backward.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{EncoderState, Mapping};
+    /// let mut state = EncoderState::new();
+    /// let mut buf = vec![];
+    /// Mapping::new(0, 0).with_source(0, 0, 0).encode_relative(&mut state, &mut buf).unwrap();
+    /// Mapping::new(0, 4).with_source(0, 1, 2).encode_relative(&mut state, &mut buf).unwrap();
+    /// Mapping::new(2, 1).encode_relative(&mut state, &mut buf).unwrap();
+    /// assert_eq!(buf, b"AAAA,IACE;;C");
+    /// ```
+    pub fn encode_relative<W>(&self, state: &mut EncoderState, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let generated = self.generated;
+        if generated.line < state.generated.line {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the mapping is on a line before the last encoded mapping",
+            ));
+        }
+
+        let mut prev_generated_col = state.generated.column;
+        if generated.line != state.generated.line {
+            prev_generated_col = 0;
+            for _ in state.generated.line..generated.line {
+                writer.write_all(&[b';'])?;
+            }
+        } else if state.started {
+            writer.write_all(&[b','])?;
+        }
+        state.started = true;
+        state.generated = generated;
+
+        let mut encoder = VlqEncoder::new(writer);
+        encoder.encode(prev_generated_col, generated.column)?;
+
+        if let Some(source_info) = self.source_info() {
+            encoder.encode(state.source_id, source_info.id)?;
+            encoder.encode(state.source_position.line, source_info.position.line)?;
+            encoder.encode(state.source_position.column, source_info.position.column)?;
+            state.source_id = source_info.id;
+            state.source_position = source_info.position;
+
+            if let Some(name_id) = self.name_id() {
+                encoder.encode(state.name_id, name_id)?;
+                state.name_id = name_id;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `MappingsEncoder` encodes [Mapping] entries one at a time into the `mappings` field,
+/// e.g. while walking an AST, without collecting them into [Mappings](crate::Mappings).
+///
+/// It wraps a writer along with an [EncoderState], see [Mapping::encode_relative].
+///
+/// # Example
+/// ```
+/// # use sora::{Mapping, MappingsEncoder};
+/// let mut encoder = MappingsEncoder::new(vec![]);
+/// encoder.write_mapping(&Mapping::new(0, 0).with_source(0, 0, 0)).unwrap();
+/// encoder.write_mapping(&Mapping::new(1, 2).with_source(0, 1, 0)).unwrap();
+/// assert_eq!(encoder.finish().unwrap(), b"AAAA;EACA");
+/// ```
+#[derive(Debug)]
+pub struct MappingsEncoder<W> {
+    writer: W,
+    state: EncoderState,
+}
+
+impl<W> MappingsEncoder<W>
+where
+    W: Write,
+{
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            state: EncoderState::new(),
+        }
+    }
+
+    /// Encodes the next mapping. Mappings must be written in generated order, see
+    /// [Mapping::encode_relative] for the errors.
+    #[inline]
+    pub fn write_mapping(&mut self, mapping: &Mapping) -> io::Result<()> {
+        mapping.encode_relative(&mut self.state, &mut self.writer)
+    }
+
+    /// Returns the running state, e.g. the generated position of the last written mapping.
+    #[inline]
+    pub fn state(&self) -> &EncoderState {
+        &self.state
+    }
+
+    /// Flushes and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mapping, Position};
+    use crate::mappings::MappingsDecoder;
+    use crate::vlq::{VlqAlphabet, VlqDecoder};
+
+    #[test]
+    fn test_position_arithmetic() {
+        let pos = Position::new(1, 5);
+        // component-wise, adding lines doesn't reset the column
+        assert_eq!(pos + (1, 2), Position::new(2, 7));
+        assert_eq!(pos + (0, 0), pos);
+        assert_eq!(pos - (1, 2), Position::new(0, 3));
+        // saturating
+        assert_eq!(Position::max() + (1, 1), Position::max());
+        assert_eq!(pos - (2, 6), Position::min());
+        assert_eq!(
+            Position::new(u32::MAX, 0) + (1, 1),
+            Position::new(u32::MAX, 1)
+        );
+
+        assert_eq!(pos.checked_add((1, 2)), Some(Position::new(2, 7)));
+        assert_eq!(pos.checked_add((0, u32::MAX)), None);
+        assert_eq!(pos.checked_sub((1, 5)), Some(Position::min()));
+        assert_eq!(pos.checked_sub((2, 0)), None);
+    }
+
+    #[test]
+    fn test_as_array() {
+        for segment in ["E", "EAAA", "EAAAA", "ECGKM"] {
+            let decoded = VlqDecoder::with_alphabet(&VlqAlphabet::STANDARD)
+                .decode(segment)
+                .unwrap()
+                .to_vec();
+            let mappings = MappingsDecoder::new(segment)
+                .items_count(10, 10)
+                .decode()
+                .unwrap();
+            assert_eq!(mappings[0].as_array(), decoded);
+        }
+    }
+
+    #[test]
+    fn test_same_generated_line() {
+        let mapping = Mapping::new(1, 5).with_source(0, 3, 0);
+        assert!(mapping.same_generated_line(&Mapping::new(1, 0)));
+        assert!(mapping.same_generated_line(&Mapping::new(1, 9).with_source(1, 0, 0)));
+        assert!(mapping.same_generated_line(&mapping));
+        assert!(!mapping.same_generated_line(&Mapping::new(0, 5)));
+        // source positions are irrelevant
+        assert!(!mapping.same_generated_line(&Mapping::new(3, 0).with_source(0, 1, 0)));
+    }
+
+    #[test]
+    fn test_is_source_less() {
+        assert!(Mapping::new(1, 0).is_source_less());
+        assert!(!Mapping::new(1, 0).with_source(0, 0, 0).is_source_less());
+        assert!(!Mapping::new(1, 0)
+            .with_source(0, 0, 0)
+            .with_name(0)
+            .is_source_less());
+
+        let mappings = MappingsDecoder::new("AAAA;;")
+            .items_count(1, 0)
+            .decode()
+            .unwrap();
+        assert_eq!(mappings.last(), Some(&Mapping::new(2, 0)));
+        assert!(mappings.last().unwrap().is_source_less());
+    }
+
+    #[test]
+    fn test_mapping_ord() {
+        let sorted = vec![
+            Mapping::new(0, 0),
+            Mapping::new(0, 0).with_source(0, 0, 0),
+            Mapping::new(0, 0).with_source(0, 0, 0).with_name(0),
+            Mapping::new(0, 0).with_source(0, 0, 0).with_name(1),
+            Mapping::new(0, 0).with_source(0, 1, 0),
+            Mapping::new(0, 0).with_source(1, 0, 0),
+            Mapping::new(0, 1),
+            Mapping::new(1, 0).with_source(0, 0, 0),
+        ];
+        let mut shuffled = vec![
+            sorted[5].clone(),
+            sorted[7].clone(),
+            sorted[0].clone(),
+            sorted[3].clone(),
+            sorted[6].clone(),
+            sorted[1].clone(),
+            sorted[4].clone(),
+            sorted[2].clone(),
+        ];
+        shuffled.sort();
+        assert_eq!(shuffled, sorted);
+    }
+
+    #[test]
+    fn test_position_checked_add() {
+        let pos = Position::new(u32::MAX - 1, u32::MAX - 1);
+        assert_eq!(
+            pos.checked_add_line(1),
+            Some(Position::new(u32::MAX, u32::MAX - 1))
+        );
+        assert_eq!(pos.checked_add_line(2), None);
+        assert_eq!(
+            pos.checked_add_column(1),
+            Some(Position::new(u32::MAX - 1, u32::MAX))
+        );
+        assert_eq!(pos.checked_add_column(2), None);
+        assert_eq!(Position::max().checked_add_line(0), Some(Position::max()));
+    }
+
+    #[test]
+    fn test_position_saturating_add() {
+        let pos = Position::new(u32::MAX - 1, u32::MAX - 1);
+        assert_eq!(
+            pos.saturating_add_line(1),
+            Position::new(u32::MAX, u32::MAX - 1)
+        );
+        assert_eq!(
+            pos.saturating_add_line(u32::MAX),
+            Position::new(u32::MAX, u32::MAX - 1)
+        );
+        assert_eq!(
+            pos.saturating_add_column(u32::MAX),
+            Position::new(u32::MAX - 1, u32::MAX)
+        );
+    }
 }
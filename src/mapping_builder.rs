@@ -0,0 +1,88 @@
+//! A typestate builder of [Mapping], created by [Mapping::builder], which checks the validity
+//! of the mapping at compile time.
+//!
+//! A name can only be set after the source information is set, since a mapping with a name
+//! but without source is invalid.
+//!
+//! ```
+//! # use sora::Mapping;
+//! let mapping = Mapping::builder(0, 4).source(0, 1, 2).name(3).build();
+//! assert_eq!(mapping, Mapping::new(0, 4).with_source(0, 1, 2).with_name(3));
+//! ```
+//!
+//! Setting a name without source doesn't compile:
+//!
+//! ```compile_fail,E0599
+//! # use sora::Mapping;
+//! let _ = Mapping::builder(0, 0).name(0).build();
+//! ```
+
+use crate::Mapping;
+use std::marker::PhantomData;
+
+/// Typestate of a [MappingBuilder] that has no source information.
+#[derive(Debug, Clone, Copy)]
+pub struct NoSource;
+
+/// Typestate of a [MappingBuilder] that has source information.
+#[derive(Debug, Clone, Copy)]
+pub struct HasSource;
+
+/// `MappingBuilder` constructs a [Mapping] with its validity checked at compile time, see the
+/// [module](self) for an example.
+#[derive(Debug, Clone)]
+pub struct MappingBuilder<S> {
+    mapping: Mapping,
+    state: PhantomData<S>,
+}
+
+impl Mapping {
+    /// Creates a [MappingBuilder] for a mapping at the given generated position, see the
+    /// [module](crate::mapping_builder) for the details.
+    #[inline(always)]
+    pub const fn builder(generated_line: u32, generated_col: u32) -> MappingBuilder<NoSource> {
+        MappingBuilder {
+            mapping: Mapping::new(generated_line, generated_col),
+            state: PhantomData,
+        }
+    }
+}
+
+impl MappingBuilder<NoSource> {
+    #[inline(always)]
+    pub const fn source(
+        self,
+        source_id: u32,
+        source_line: u32,
+        source_col: u32,
+    ) -> MappingBuilder<HasSource> {
+        MappingBuilder {
+            mapping: self.mapping.with_source(source_id, source_line, source_col),
+            state: PhantomData,
+        }
+    }
+}
+
+impl MappingBuilder<HasSource> {
+    #[inline(always)]
+    pub const fn name(self, name_id: u32) -> Self {
+        Self {
+            mapping: self.mapping.with_name(name_id),
+            state: PhantomData,
+        }
+    }
+}
+
+impl<S> MappingBuilder<S> {
+    #[inline(always)]
+    pub const fn build(self) -> Mapping {
+        self.mapping
+    }
+}
+
+impl<S> From<MappingBuilder<S>> for Mapping {
+    #[inline(always)]
+    fn from(builder: MappingBuilder<S>) -> Self {
+        builder.build()
+    }
+}
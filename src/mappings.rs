@@ -1,15 +1,15 @@
-use crate::finder::{MappingFinder, MappingFinderImpl};
+use crate::finder::{CachingFinder, LookupTable, MappingFinder, MappingFinderImpl};
 use crate::hint::{likely, unlikely};
-use crate::mapping::{Mapping, Position};
+use crate::mapping::{Mapping, MappingsEncoder, Position, SourceInfo};
 use crate::splitter::MappingSplitter;
-use crate::vlq::{VlqDecoder, VlqEncoder};
-use crate::{ParseError, ParseResult, ValidateError, ValidateResult};
+use crate::vlq::{VlqAlphabet, VlqDecoder};
+use crate::{ParseError, ParseOptions, ParseResult, ValidateError, ValidateResult};
 use std::io;
 use std::io::Write;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 
 /// `Mappings` is a collection of [Mapping] entries.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct Mappings(pub(crate) Vec<Mapping>);
 
 impl Deref for Mappings {
@@ -31,6 +31,11 @@ impl Mappings {
         v
     }
 
+    /// Creates an empty `Mappings` with space for at least `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
     /// Creates a new `Mappings` from a vec of [Mapping] entries without any check and sorting.
     ///
     /// # Safety
@@ -43,10 +48,205 @@ impl Mappings {
     }
 }
 
+/// Collects [Mapping] entries, which will be sorted like [Mappings::new].
+#[cfg(feature = "builder")]
+impl FromIterator<Mapping> for Mappings {
+    fn from_iter<T: IntoIterator<Item = Mapping>>(iter: T) -> Self {
+        Self::new(Vec::from_iter(iter))
+    }
+}
+
+/// Appends [Mapping] entries, and then sorts all the entries to keep data valid.
+#[cfg(feature = "builder")]
+impl Extend<Mapping> for Mappings {
+    fn extend<T: IntoIterator<Item = Mapping>>(&mut self, iter: T) {
+        self.0.extend(iter);
+        self.sort();
+    }
+}
+
+/// `MappingsBuilder` constructs [Mappings] from relative values, as they appear in the `mappings` field.
+///
+/// Like the decoder, the builder keeps the running state (generated column, source id, source
+/// position and name id) and accumulates the given deltas into absolute positions.
+/// A delta moving the state out of range is rejected, and the state is left unchanged.
+///
+/// # Example
+/// ```
+/// # use sora::MappingsBuilder;
+/// // equivalent to "AAAA,CAAC;ACAAA"
+/// let mut builder = MappingsBuilder::new();
+/// builder.push_generated(0)?.push_source(0, 0, 0)?;
+/// builder.push_generated(1)?.push_source(0, 0, 1)?;
+/// builder.new_line()?;
+/// builder.push_generated(0)?.push_source(1, 0, 0)?.push_name(0)?;
+/// let mappings = builder.build();
+/// assert_eq!(mappings.len(), 3);
+/// # Ok::<(), sora::ValidateError>(())
+/// ```
+#[cfg(feature = "builder")]
+#[derive(Debug, Clone, Default)]
+pub struct MappingsBuilder {
+    mappings: Vec<Mapping>,
+    /// (generated_line, generated_col, source_id, source_line, source_col, name_id)
+    state: (u32, u32, u32, u32, u32, u32),
+}
+
+#[cfg(feature = "builder")]
+impl MappingsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves to the next generated line, which resets the generated column to 0.
+    ///
+    /// Returns [PositionOverflow](ValidateError::PositionOverflow) if the line exceeds `u32`.
+    pub fn new_line(&mut self) -> ValidateResult<&mut Self> {
+        self.state.0 = self
+            .state
+            .0
+            .checked_add(1)
+            .ok_or(ValidateError::PositionOverflow)?;
+        self.state.1 = 0;
+        Ok(self)
+    }
+
+    /// Starts a new mapping at the generated column relative to the previous one on the same line.
+    ///
+    /// Like the decoder, a negative column returns
+    /// [MappingsUnordered](ValidateError::MappingsUnordered), and a column exceeding `u32` returns
+    /// [PositionOverflow](ValidateError::PositionOverflow).
+    pub fn push_generated(&mut self, generated_col: i64) -> ValidateResult<&mut Self> {
+        if generated_col < 0 {
+            return Err(ValidateError::MappingsUnordered);
+        }
+        self.state.1 = add_delta(self.state.1, generated_col, u32::MAX)
+            .ok_or(ValidateError::PositionOverflow)?;
+        self.mappings.push(Mapping::new(self.state.0, self.state.1));
+        Ok(self)
+    }
+
+    /// Adds source information, relative to the previous one, to the last pushed mapping.
+    ///
+    /// Returns [StateOutOfRange](ValidateError::StateOutOfRange) if the source id becomes
+    /// negative or reaches `u32::MAX`, which is reserved for a missing id, or if the source
+    /// position leaves the `u32` range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no mapping has been pushed by [push_generated](Self::push_generated).
+    pub fn push_source(
+        &mut self,
+        source_id: i64,
+        source_line: i64,
+        source_col: i64,
+    ) -> ValidateResult<&mut Self> {
+        let (Some(source_id), Some(source_line), Some(source_col)) = (
+            add_delta(self.state.2, source_id, u32::MAX - 1),
+            add_delta(self.state.3, source_line, u32::MAX),
+            add_delta(self.state.4, source_col, u32::MAX),
+        ) else {
+            return Err(ValidateError::StateOutOfRange);
+        };
+        let mapping = self.last_mut();
+        *mapping = mapping
+            .clone()
+            .with_source(source_id, source_line, source_col);
+        (self.state.2, self.state.3, self.state.4) = (source_id, source_line, source_col);
+        Ok(self)
+    }
+
+    /// Adds a name, relative to the previous one, to the last pushed mapping.
+    ///
+    /// Returns [StateOutOfRange](ValidateError::StateOutOfRange) if the name id becomes negative
+    /// or reaches `u32::MAX`, which is reserved for a missing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the last pushed mapping has no source information.
+    pub fn push_name(&mut self, name_id: i64) -> ValidateResult<&mut Self> {
+        let name_id =
+            add_delta(self.state.5, name_id, u32::MAX - 1).ok_or(ValidateError::StateOutOfRange)?;
+        let mapping = self.last_mut();
+        assert!(mapping.has_source(), "a name requires source information");
+        *mapping = mapping.clone().with_name(name_id);
+        self.state.5 = name_id;
+        Ok(self)
+    }
+
+    /// Creates the [Mappings], sorted by generated positions.
+    pub fn build(self) -> Mappings {
+        Mappings::new(self.mappings)
+    }
+
+    fn last_mut(&mut self) -> &mut Mapping {
+        self.mappings
+            .last_mut()
+            .expect("no mapping has been pushed")
+    }
+}
+
+/// Applies a relative value, returning `None` if the result is negative or exceeds `max`.
+#[cfg(feature = "builder")]
+fn add_delta(value: u32, delta: i64, max: u32) -> Option<u32> {
+    let value = (value as i64).checked_add(delta)?;
+    u32::try_from(value).ok().filter(|&value| value <= max)
+}
+
 impl Mappings {
     /// Sorts mapping entries by their generated positions to ensure data valid.
+    ///
+    /// Entries at the same generated position are sorted by the rest of the
+    /// [order](Mapping#impl-Ord-for-Mapping), so the result is deterministic.
     pub fn sort(&mut self) {
-        self.0.sort_unstable_by_key(Mapping::generated)
+        self.0.sort_unstable()
+    }
+
+    /// Removes the redundant mappings, i.e. the ones with the same source information and name
+    /// as the previous mapping on the same generated line.
+    ///
+    /// Finding any position results in the same source information and name before and after,
+    /// while the mappings are encoded to a shorter string.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let mut sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAA,CAAC"}"#.to_vec()).unwrap();
+    /// sm.mappings_mut().compact();
+    /// assert_eq!(sm.mappings().to_encoded_string(), "AAAA,EAAC");
+    /// ```
+    pub fn compact(&mut self) {
+        self.0.dedup_by(|cur, prev| {
+            cur.same_generated_line(prev)
+                && cur.source_info() == prev.source_info()
+                && cur.name_id() == prev.name_id()
+        })
+    }
+
+    /// Compares the generated positions of each adjacent pair of mappings, and returns the index
+    /// of the first mapping of each pair along with the result.
+    ///
+    /// This is a debugging aid for generators: an entry with [Greater](std::cmp::Ordering::Greater)
+    /// pinpoints where the output went non-monotonic, before [sort](Self::sort) masks it.
+    /// Only available with `debug_assertions`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// # use sora::SourceMap;
+    /// # use std::cmp::Ordering;
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A,C;A"}"#.to_vec()).unwrap();
+    /// assert_eq!(
+    ///     sm.mappings().ordering_report(),
+    ///     vec![(0, Ordering::Less), (1, Ordering::Less)]
+    /// );
+    /// ```
+    #[cfg(debug_assertions)]
+    pub fn ordering_report(&self) -> Vec<(usize, std::cmp::Ordering)> {
+        self.0
+            .windows(2)
+            .enumerate()
+            .map(|(idx, pair)| (idx, pair[0].generated().cmp(&pair[1].generated())))
+            .collect()
     }
 
     /// Provides mutable access to the internal vec of [Mapping] entries.
@@ -59,9 +259,333 @@ impl Mappings {
     pub unsafe fn inner_mut(&mut self) -> &mut Vec<Mapping> {
         &mut self.0
     }
+
+    /// Appends the mappings of `other`, with their generated lines shifted by `line_offset`,
+    /// and their source ids and name ids rebased on `source_id_base` and `name_id_base`.
+    ///
+    /// This is the primitive to concatenate source maps: the sources and names of the other map
+    /// are expected to be appended to the ones of this map, starting at the given bases.
+    ///
+    /// The shifted mappings must not precede the last mapping of this collection, which usually
+    /// means `line_offset` should exceed the current last generated line.
+    /// Otherwise, [MappingsUnordered](ValidateError::MappingsUnordered) is returned,
+    /// or [PositionOverflow](ValidateError::PositionOverflow) if a shifted line exceeds `u32`.
+    /// If a rebased id exceeds the id range,
+    /// [UnknownSourceReference](ValidateError::UnknownSourceReference) or
+    /// [UnknownNameReference](ValidateError::UnknownNameReference) is returned with the original
+    /// id. Nothing is appended on error.
+    pub fn extend_offset(
+        &mut self,
+        other: &Mappings,
+        line_offset: u32,
+        source_id_base: u32,
+        name_id_base: u32,
+    ) -> ValidateResult<()> {
+        if let Some(first) = other.first() {
+            let first_line = first
+                .generated()
+                .line
+                .checked_add(line_offset)
+                .ok_or(ValidateError::PositionOverflow)?;
+            if let Some(last) = self.last() {
+                if Position::new(first_line, first.generated().column) < last.generated() {
+                    return Err(ValidateError::MappingsUnordered);
+                }
+            }
+        }
+        if let Some(last) = other.last() {
+            last.generated()
+                .line
+                .checked_add(line_offset)
+                .ok_or(ValidateError::PositionOverflow)?;
+        }
+        // `u32::MAX` is reserved for a missing id
+        let overflows = |id: u32, base: u32| base >= u32::MAX - id;
+        for mapping in other.iter() {
+            if let Some(source_id) = mapping.source_id() {
+                if overflows(source_id, source_id_base) {
+                    return Err(ValidateError::UnknownSourceReference(source_id));
+                }
+            }
+            if let Some(name_id) = mapping.name_id() {
+                if overflows(name_id, name_id_base) {
+                    return Err(ValidateError::UnknownNameReference(name_id));
+                }
+            }
+        }
+
+        self.0.reserve(other.len());
+        self.0.extend(other.iter().map(|mapping| {
+            let generated = mapping.generated();
+            let mut shifted = Mapping::new(generated.line + line_offset, generated.column);
+            if let Some(source_info) = mapping.source_info() {
+                shifted = shifted.with_source(
+                    source_info.id + source_id_base,
+                    source_info.position.line,
+                    source_info.position.column,
+                );
+                if let Some(name_id) = mapping.name_id() {
+                    shifted = shifted.with_name(name_id + name_id_base);
+                }
+            }
+            shifted
+        }));
+
+        Ok(())
+    }
+
+    /// Adds `base` to the name id of every mapping, e.g. after the names this collection
+    /// references have been appended to another list of names at index `base`.
+    ///
+    /// [UnknownNameReference](ValidateError::UnknownNameReference) is returned with the original
+    /// id if a rebased id exceeds the id range. Nothing is changed on error.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"names":["x"],"mappings":"AAAAA"}"#.to_vec()).unwrap();
+    /// let mut mappings = sm.mappings().clone();
+    /// mappings.rebase_names(2).unwrap();
+    /// assert_eq!(mappings[0].name_id(), Some(2));
+    /// ```
+    pub fn rebase_names(&mut self, base: u32) -> ValidateResult<()> {
+        // `u32::MAX` is reserved for a missing name
+        if let Some(name_id) = self
+            .iter()
+            .filter_map(Mapping::name_id)
+            .find(|&id| base >= u32::MAX - id)
+        {
+            return Err(ValidateError::UnknownNameReference(name_id));
+        }
+        for mapping in self.0.iter_mut() {
+            if let Some(name_id) = mapping.name_id() {
+                *mapping = mapping.clone().with_name(name_id + base);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Mappings {
+    /// Returns the number of [Mapping] entries.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A,C;A"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.mappings().len(), 3);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no [Mapping] entries.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":""}"#.to_vec()).unwrap();
+    /// assert!(sm.mappings().is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of [Mapping] entries the collection can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Removes all [Mapping] entries, keeping the allocated capacity for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns the [Mapping] entry at the index, or `None` if out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Mapping, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A,C;A"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.mappings().get(1), Some(&Mapping::new(0, 1)));
+    /// assert_eq!(sm.mappings().get(3), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<&Mapping> {
+        self.0.get(idx)
+    }
+
+    /// Encodes the mappings into the VLQ string of the `mappings` field.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A,C;A"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.mappings().to_encoded_string(), "A,C;A");
+    /// ```
+    pub fn to_encoded_string(&self) -> String {
+        let mut buf = Vec::new();
+        // writing into a vec never fails, and the mappings are sorted
+        self.encode(&mut buf).unwrap();
+        // SAFETY: the encoder only writes base64 characters and separators
+        unsafe { String::from_utf8_unchecked(buf) }
+    }
+
+    /// Returns the byte length of the encoded `mappings` field (without quotes),
+    /// without allocating the encoded string.
+    pub fn encoded_len(&self) -> usize {
+        let mut counter = ByteCounter(0);
+        // counting never fails
+        self.encode(&mut counter).unwrap();
+        counter.0
+    }
+
+    /// Checks that the mappings can be encoded for any consumer, i.e. each VLQ value of the
+    /// encoded `mappings`, which is the delta from the previous segment, fits in the 32-bit
+    /// signed range. Consumers written in JavaScript decode VLQ values with 32-bit integer
+    /// arithmetic, so larger values are silently corrupted, even though sora itself encodes and
+    /// decodes them correctly.
+    ///
+    /// Fails with [DeltaOverflow](ValidateError::DeltaOverflow) at the generated position of the
+    /// first mapping with an oversized delta, or with
+    /// [MappingsUnordered](ValidateError::MappingsUnordered) if a generated line goes backward.
+    pub fn check_encodable(&self) -> ValidateResult<()> {
+        let fits = |prev: u32, value: u32| i32::try_from(value as i64 - prev as i64).is_ok();
+
+        let mut prev_generated = Position::min();
+        let (mut prev_source_id, mut prev_source_position, mut prev_name_id) =
+            (0, Position::min(), 0);
+        for mapping in &self.0 {
+            let generated = mapping.generated();
+            if generated.line < prev_generated.line {
+                return Err(ValidateError::MappingsUnordered);
+            }
+            let prev_col = if generated.line == prev_generated.line {
+                prev_generated.column
+            } else {
+                0
+            };
+            let mut encodable = fits(prev_col, generated.column);
+            prev_generated = generated;
+
+            if let Some(source_info) = mapping.source_info() {
+                let position = source_info.position;
+                encodable &= fits(prev_source_id, source_info.id)
+                    && fits(prev_source_position.line, position.line)
+                    && fits(prev_source_position.column, position.column);
+                prev_source_id = source_info.id;
+                prev_source_position = position;
+            }
+            if let Some(name_id) = mapping.name_id() {
+                encodable &= fits(prev_name_id, name_id);
+                prev_name_id = name_id;
+            }
+
+            if !encodable {
+                return Err(ValidateError::DeltaOverflow(generated));
+            }
+        }
+        Ok(())
+    }
+
+    /// see [contains_position](crate::BorrowedSourceMap::contains_position).
+    pub fn contains_position<P>(&self, pos: P) -> bool
+    where
+        P: Into<Position>,
+    {
+        let pos = pos.into();
+        match (self.first(), self.last()) {
+            (Some(first), Some(last)) => first.generated() <= pos && pos <= last.generated(),
+            _ => false,
+        }
+    }
+
+    /// Returns the mappings on the given generated line.
+    pub fn line_mappings(&self, line: u32) -> &[Mapping] {
+        &self[self.lines_range(line, line)]
+    }
+
+    /// Returns an iterator over the generated lines with their mappings, in order.
+    ///
+    /// Only lines with at least one mapping are yielded, empty lines are skipped.
+    /// Each slice equals the one returned by [line_mappings](Self::line_mappings) for the line.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A,C;;A"}"#.to_vec()).unwrap();
+    /// let lines = sm.mappings().by_line().map(|(line, m)| (line, m.len())).collect::<Vec<_>>();
+    /// assert_eq!(lines, vec![(0, 2), (2, 1)]);
+    /// ```
+    pub fn by_line(&self) -> impl Iterator<Item = (u32, &[Mapping])> + '_ {
+        let mut rest = &self.0[..];
+        std::iter::from_fn(move || {
+            let line = rest.first()?.generated().line;
+            let len = rest.partition_point(|m| m.generated().line <= line);
+            let (current, next) = rest.split_at(len);
+            rest = next;
+            Some((line, current))
+        })
+    }
+
+    /// Returns an iterator over the generated positions where the source id changes from the
+    /// previous mapping, along with the new source id, or `None` for the mappings without
+    /// source information.
+    ///
+    /// The first mapping is always yielded, so the result segments the generated file by the
+    /// originating sources: each region spans from its position to the next one.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Position, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA,CAAC,CCAC;C"}"#.to_vec()).unwrap();
+    /// let transitions = sm.mappings().source_transitions().collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     transitions,
+    ///     vec![(Position::new(0, 0), Some(0)), (Position::new(0, 2), Some(1)), (Position::new(1, 1), None)]
+    /// );
+    /// ```
+    pub fn source_transitions(&self) -> impl Iterator<Item = (Position, Option<u32>)> + '_ {
+        let mut prev = None;
+        self.0.iter().filter_map(move |mapping| {
+            let source_id = mapping.source_id();
+            if prev == Some(source_id) {
+                return None;
+            }
+            prev = Some(source_id);
+            Some((mapping.generated(), source_id))
+        })
+    }
+
+    /// Returns an iterator over the generated lines with mappings, along with the number of
+    /// mappings on each, in ascending order of lines. The lines without mappings are skipped.
+    ///
+    /// This helps to spot generator anomalies, like a single line with a huge number of segments.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A,C;;A"}"#.to_vec()).unwrap();
+    /// let counts = sm.mappings().segment_counts_per_line().collect::<Vec<_>>();
+    /// assert_eq!(counts, vec![(0, 2), (2, 1)]);
+    /// ```
+    pub fn segment_counts_per_line(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.by_line()
+            .map(|(line, mappings)| (line, mappings.len() as u32))
+    }
+
+    /// Returns the index range of the mappings on generated lines
+    /// from `first_line` to `last_line` (both inclusive).
+    pub(crate) fn lines_range(&self, first_line: u32, last_line: u32) -> Range<usize> {
+        let start = self.partition_point(|m| m.generated().line < first_line);
+        let end = start + self[start..].partition_point(|m| m.generated().line <= last_line);
+        start..end
+    }
+
     /// see [find_mapping](crate::BorrowedSourceMap::find_mapping).
     pub fn find_mapping<P>(&self, pos: P) -> Option<Mapping>
     where
@@ -70,10 +594,103 @@ impl Mappings {
         MappingFinderImpl::new(self).find(pos.into(), None)
     }
 
+    /// Binary searches the mappings for the generated position, like
+    /// [binary_search_by_key](slice::binary_search_by_key) with [Mapping::generated] as the key.
+    ///
+    /// It returns `Ok` with the index of a mapping at exactly the position, or `Err` with the
+    /// index where a mapping at the position would be inserted. If several mappings are at the
+    /// position, any one of them may be returned. This is the primitive behind the finders,
+    /// without their heuristics, for custom lookups.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,EAAE;AACA"}"#.to_vec()).unwrap();
+    /// let mappings = sm.mappings();
+    /// assert_eq!(mappings.binary_search_generated((0, 2)), Ok(1));
+    /// // the closest preceding mapping is at `idx - 1`
+    /// assert_eq!(mappings.binary_search_generated((0, 1)), Err(1));
+    /// assert_eq!(mappings.binary_search_generated((9, 0)), Err(3));
+    /// ```
+    #[inline]
+    pub fn binary_search_generated<P>(&self, pos: P) -> Result<usize, usize>
+    where
+        P: Into<Position>,
+    {
+        self.binary_search_by_key(&pos.into(), Mapping::generated)
+    }
+
+    /// see [surrounding_mappings](crate::BorrowedSourceMap::surrounding_mappings).
+    pub fn surrounding_mappings<P>(&self, pos: P) -> (Option<Mapping>, Option<Mapping>)
+    where
+        P: Into<Position>,
+    {
+        let pos = pos.into();
+        let idx = self.partition_point(|m| m.generated() <= pos);
+        let before = idx.checked_sub(1).and_then(|idx| self.get(idx)).cloned();
+        (before, self.get(idx).cloned())
+    }
+
+    /// see [original_span](crate::BorrowedSourceMap::original_span).
+    pub fn original_span<P>(&self, pos: P) -> Option<(SourceInfo, SourceInfo)>
+    where
+        P: Into<Position>,
+    {
+        let pos = pos.into();
+        let idx = self
+            .partition_point(|m| m.generated() <= pos)
+            .checked_sub(1)?;
+        let start = self[idx].source_info()?;
+        let end = self[idx + 1..]
+            .iter()
+            .filter_map(Mapping::source_info)
+            .find(|info| info.id == start.id)?;
+        if end.position < start.position {
+            return None;
+        }
+        Some((start, end))
+    }
+
+    /// see [rfind_mapping](crate::BorrowedSourceMap::rfind_mapping).
+    pub fn rfind_mapping<P>(&self, pos: P) -> Option<Mapping>
+    where
+        P: Into<Position>,
+    {
+        let pos = pos.into();
+        let idx = self.partition_point(|m| m.generated() < pos);
+        self.get(idx).cloned()
+    }
+
     /// see [find_mapping](crate::BorrowedSourceMap::finder).
     pub fn finder(&self) -> MappingFinder {
         MappingFinder::new(self)
     }
+
+    /// see [caching_finder](crate::BorrowedSourceMap::caching_finder).
+    pub fn caching_finder(&self, capacity: usize) -> CachingFinder {
+        CachingFinder::new(self, capacity)
+    }
+
+    /// see [lookup_table](crate::BorrowedSourceMap::lookup_table).
+    pub fn lookup_table(&self) -> LookupTable {
+        LookupTable::new(self)
+    }
+}
+
+/// A writer which discards the data and counts the bytes written.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -88,53 +705,93 @@ impl ItemsCount {
     }
 }
 
+const BINARY_MAGIC: &[u8; 4] = b"SORA";
+const BINARY_VERSION: u32 = 1;
+// magic, version, count of mappings
+const BINARY_HEADER_LEN: usize = 4 + 4 + 8;
+// 6 packed u32 fields
+const BINARY_MAPPING_LEN: usize = 6 * 4;
+
 impl Mappings {
-    pub(crate) fn encode<W>(&self, writer: &mut W) -> io::Result<()>
-    where
-        W: Write,
-    {
-        let mut prev_generated_line = 0;
-        let mut prev_generated_col = 0;
-        let mut prev_source_id = 0;
-        let mut prev_source_line = 0;
-        let mut prev_source_col = 0;
-        let mut prev_name_id = 0;
-
-        for (idx, mapping) in self.0.iter().enumerate() {
-            let generated_pos = mapping.generated();
-
-            if generated_pos.line != prev_generated_line {
-                prev_generated_col = 0;
-                while generated_pos.line != prev_generated_line {
-                    writer.write_all(&[b';'])?;
-                    prev_generated_line += 1;
-                }
-            } else if idx != 0 {
-                writer.write_all(&[b','])?;
+    /// Serializes the mappings into a compact binary blob, which can be cached and loaded by
+    /// [from_bytes](Self::from_bytes) without decoding the VLQ again.
+    ///
+    /// The layout is fixed and independent of the platform: a header of the magic `SORA`,
+    /// the format version and the count of mappings, followed by the fields of each mapping,
+    /// all numbers in little-endian.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Mappings, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;AACA"}"#.to_vec()).unwrap();
+    /// let bytes = sm.mappings().to_bytes();
+    /// assert_eq!(&Mappings::from_bytes(&bytes).unwrap(), sm.mappings());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BINARY_HEADER_LEN + self.len() * BINARY_MAPPING_LEN);
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.len() as u64).to_le_bytes());
+        for mapping in self.iter() {
+            for field in mapping.to_packed() {
+                bytes.extend_from_slice(&field.to_le_bytes());
             }
+        }
+        bytes
+    }
 
-            let mut encoder = VlqEncoder::new(writer);
-
-            encoder.encode(prev_generated_col, generated_pos.column)?;
-            prev_generated_col = generated_pos.column;
-
-            if let Some(source_info) = mapping.source_info() {
-                encoder.encode(prev_source_id, source_info.id)?;
-                prev_source_id = source_info.id;
-
-                encoder.encode(prev_source_line, source_info.position.line)?;
-                prev_source_line = source_info.position.line;
-
-                encoder.encode(prev_source_col, source_info.position.column)?;
-                prev_source_col = source_info.position.column;
+    /// Deserializes the mappings from a binary blob created by [to_bytes](Self::to_bytes).
+    ///
+    /// The blob is fully checked, so truncated or garbage input results in
+    /// [InvalidBinary](ParseError::InvalidBinary), and unordered mappings in
+    /// [MappingsUnordered](ParseError::MappingsUnordered). Note that the references to sources
+    /// and names cannot be checked without the source map.
+    pub fn from_bytes(bytes: &[u8]) -> ParseResult<Self> {
+        if unlikely!(bytes.len() < BINARY_HEADER_LEN || &bytes[..4] != BINARY_MAGIC) {
+            return Err(ParseError::InvalidBinary("bad header"));
+        }
+        let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        if unlikely!(read_u32(4) != BINARY_VERSION) {
+            return Err(ParseError::InvalidBinary("unsupported version"));
+        }
+        let count = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+        let expected_len = usize::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(BINARY_MAPPING_LEN))
+            .and_then(|len| len.checked_add(BINARY_HEADER_LEN));
+        if unlikely!(expected_len != Some(bytes.len())) {
+            return Err(ParseError::InvalidBinary("length mismatch"));
+        }
 
-                if let Some(name_id) = mapping.name_id() {
-                    encoder.encode(prev_name_id, name_id)?;
-                    prev_name_id = name_id;
-                }
+        let mut buffer = Vec::new();
+        buffer.try_reserve_exact(count as usize)?;
+        let mut last_generated_pos = Position::min();
+        for chunk in bytes[BINARY_HEADER_LEN..].chunks_exact(BINARY_MAPPING_LEN) {
+            let mut packed = [0; 6];
+            for (idx, field) in packed.iter_mut().enumerate() {
+                *field = u32::from_le_bytes(chunk[idx * 4..idx * 4 + 4].try_into().unwrap());
             }
+            let mapping = Mapping::from_packed(packed)
+                .ok_or(ParseError::InvalidBinary("name without source"))?;
+            if unlikely!(mapping.generated() < last_generated_pos) {
+                return Err(ParseError::MappingsUnordered);
+            }
+            last_generated_pos = mapping.generated();
+            buffer.push(mapping);
         }
+        Ok(Self(buffer))
+    }
+}
 
+impl Mappings {
+    pub(crate) fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let mut encoder = MappingsEncoder::new(writer);
+        for mapping in &self.0 {
+            encoder.write_mapping(mapping)?;
+        }
         Ok(())
     }
 
@@ -167,21 +824,58 @@ impl Mappings {
 
         Ok(())
     }
+
+    /// Validates that source ids and name ids are in range, without checking the order.
+    pub(crate) fn validate_references(&self, items_count: ItemsCount) -> ValidateResult<()> {
+        for mapping in &self.0 {
+            if let Some(source_id) = mapping.source_id() {
+                if source_id >= items_count.sources {
+                    return Err(ValidateError::UnknownSourceReference(source_id));
+                }
+            }
+            if let Some(name_id) = mapping.name_id() {
+                if name_id >= items_count.names {
+                    return Err(ValidateError::UnknownNameReference(name_id));
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Mappings {
-    pub(crate) fn empty() -> Self {
-        Self(Vec::with_capacity(256))
+    /// Creates an empty collection with some capacity reserved for decoding.
+    pub(crate) fn empty() -> ParseResult<Self> {
+        let mut buffer = Vec::new();
+        try_reserve(&mut buffer, 256)?;
+        Ok(Self(buffer))
     }
 }
 
-#[derive(Default)]
 pub(crate) struct MappingsDecoder<'a> {
     source: &'a str,
     items_count: ItemsCount,
+    alphabet: &'a VlqAlphabet,
+    max_mappings: usize,
+    insert_end_sentinel: bool,
     #[cfg(feature = "index-map")]
     /// (generated_line, generated_col, source_id, name_id)
     state: (u32, u32, u32, u32),
+    /// The number of end sentinels already in the buffer, which don't count to `max_mappings`.
+    #[cfg(feature = "index-map")]
+    sentinels: usize,
+}
+
+/// The end of the mappings decoded by [decode_filtered_into](MappingsDecoder::decode_filtered_into).
+// only read by the index map
+#[cfg_attr(not(feature = "index-map"), allow(dead_code))]
+pub(crate) struct DecodedEnd {
+    /// The generated position of the last decoded mapping, i.e. the end sentinel or else the
+    /// last segment, whether kept or not. `None` if there is no segment.
+    pub position: Option<Position>,
+    /// Whether the end sentinel has been appended.
+    pub sentinel: bool,
 }
 
 impl<'a> MappingsDecoder<'a> {
@@ -189,10 +883,25 @@ impl<'a> MappingsDecoder<'a> {
     pub(crate) fn new(source: &'a str) -> Self {
         Self {
             source,
-            ..Default::default()
+            items_count: ItemsCount::default(),
+            alphabet: &VlqAlphabet::STANDARD,
+            max_mappings: usize::MAX,
+            insert_end_sentinel: true,
+            #[cfg(feature = "index-map")]
+            state: (0, 0, 0, 0),
+            #[cfg(feature = "index-map")]
+            sentinels: 0,
         }
     }
 
+    #[inline]
+    pub(crate) fn options(mut self, options: &'a ParseOptions) -> Self {
+        self.alphabet = &options.alphabet;
+        self.max_mappings = options.max_mappings.unwrap_or(usize::MAX);
+        self.insert_end_sentinel = options.insert_end_sentinel;
+        self
+    }
+
     #[inline]
     pub(crate) fn items_count(mut self, sources: u32, names: u32) -> Self {
         self.items_count = ItemsCount::new(sources, names);
@@ -211,21 +920,54 @@ impl<'a> MappingsDecoder<'a> {
         self.state = (generated_line, generated_col, source_id, name_id);
         self
     }
+
+    #[inline]
+    #[cfg(feature = "index-map")]
+    pub(crate) fn sentinels(mut self, sentinels: usize) -> Self {
+        self.sentinels = sentinels;
+        self
+    }
 }
 
 impl<'a> MappingsDecoder<'a> {
+    // only used by the builder and tests since the parsing supports filtering
+    #[cfg_attr(not(feature = "builder"), allow(dead_code))]
     pub(crate) fn decode(&self) -> ParseResult<Mappings> {
-        let mut mappings = Mappings::empty();
-        self.decode_into(&mut mappings)?;
+        self.decode_filtered(|_| true)
+    }
+
+    /// Decodes only the mappings whose generated position passes `keep`, see
+    /// [from_slice_filtered](crate::BorrowedSourceMap::from_slice_filtered).
+    pub(crate) fn decode_filtered<F>(&self, keep: F) -> ParseResult<Mappings>
+    where
+        F: FnMut(Position) -> bool,
+    {
+        let mut mappings = Mappings::empty()?;
+        self.decode_filtered_into(&mut mappings, keep)?;
         Ok(mappings)
     }
 
-    pub(crate) fn decode_into(&self, mappings: &mut Mappings) -> ParseResult<()> {
+    /// Appends the mappings which pass `keep` to `mappings`, and returns where they end.
+    ///
+    /// `max_mappings` limits the number of segments in `mappings`, excluding the end sentinels.
+    pub(crate) fn decode_filtered_into<F>(
+        &self,
+        mappings: &mut Mappings,
+        mut keep: F,
+    ) -> ParseResult<DecodedEnd>
+    where
+        F: FnMut(Position) -> bool,
+    {
         let source = self.source;
         let items_count = self.items_count;
 
         let buffer = &mut mappings.0;
 
+        #[cfg(feature = "index-map")]
+        let limit = self.max_mappings.saturating_add(self.sentinels);
+        #[cfg(not(feature = "index-map"))]
+        let limit = self.max_mappings;
+
         #[cfg(feature = "index-map")]
         let (mut generated_line, mut generated_col, mut source_id, mut name_id) = self.state;
         #[cfg(not(feature = "index-map"))]
@@ -234,7 +976,10 @@ impl<'a> MappingsDecoder<'a> {
         let mut source_line = 0;
         let mut source_col = 0;
 
-        let mut decoder = VlqDecoder::new();
+        let mut decoder = VlqDecoder::with_alphabet(self.alphabet);
+
+        // the sentinel depends on the decoded mappings, regardless of the discarded ones
+        let mut last_decoded: Option<Position> = None;
 
         let splitter = MappingSplitter::new(source);
 
@@ -242,63 +987,696 @@ impl<'a> MappingsDecoder<'a> {
             if likely!(!segment.is_empty()) {
                 let nums = decoder.decode(segment)?;
 
-                let mapping =
-                    match nums.len() {
-                        1 => {
-                            if unlikely!(nums[0] < 0) {
-                                return Err(ParseError::MappingsUnordered);
-                            }
-                            generated_col = (generated_col as i64 + nums[0]) as u32;
-                            Mapping::new(generated_line, generated_col)
-                        }
-                        4 | 5 => {
-                            if unlikely!(nums[0] < 0) {
-                                return Err(ParseError::MappingsUnordered);
-                            }
-                            generated_col = (generated_col as i64 + nums[0]) as u32;
+                let mapping = match nums.len() {
+                    1 => {
+                        generated_col = add_generated_col(generated_col, nums[0])?;
+                        Mapping::new(generated_line, generated_col)
+                    }
+                    4 | 5 => {
+                        generated_col = add_generated_col(generated_col, nums[0])?;
 
-                            source_id = (source_id as i64 + nums[1]) as u32;
-                            if unlikely!(source_id >= items_count.sources) {
-                                return Err(ParseError::UnknownSourceReference(source_id));
-                            }
+                        source_id = (source_id as i64 + nums[1]) as u32;
+                        if unlikely!(source_id >= items_count.sources) {
+                            return Err(ParseError::UnknownSourceReference(source_id));
+                        }
 
-                            source_line = (source_line as i64 + nums[2]) as u32;
-                            source_col = (source_col as i64 + nums[3]) as u32;
+                        source_line = (source_line as i64 + nums[2]) as u32;
+                        source_col = (source_col as i64 + nums[3]) as u32;
 
-                            let mut mapping = Mapping::new(generated_line, generated_col)
-                                .with_source(source_id, source_line, source_col);
+                        let mut mapping = Mapping::new(generated_line, generated_col).with_source(
+                            source_id,
+                            source_line,
+                            source_col,
+                        );
 
-                            if nums.len() == 5 {
-                                name_id = (name_id as i64 + nums[4]) as u32;
-                                if unlikely!(name_id >= items_count.names) {
-                                    return Err(ParseError::UnknownNameReference(name_id));
-                                }
-                                mapping = mapping.with_name(name_id)
+                        if nums.len() == 5 {
+                            name_id = (name_id as i64 + nums[4]) as u32;
+                            if unlikely!(name_id >= items_count.names) {
+                                return Err(ParseError::UnknownNameReference(name_id));
                             }
-
-                            mapping
+                            mapping = mapping.with_name(name_id)
                         }
-                        _ => return Err(ParseError::MappingMalformed(segment.to_owned())),
-                    };
-                buffer.push(mapping);
+
+                        mapping
+                    }
+                    found @ (2 | 3) => return Err(ParseError::MappingSegmentFieldCount { found }),
+                    _ => return Err(ParseError::MappingMalformed(segment.to_owned())),
+                };
+                // the running state has been advanced even if the mapping is discarded
+                last_decoded = Some(mapping.generated());
+                if keep(mapping.generated()) {
+                    push_mapping(buffer, mapping, limit, self.max_mappings)?;
+                }
             }
 
             if next_new_line {
-                generated_line += 1;
+                generated_line = generated_line
+                    .checked_add(1)
+                    .ok_or(ParseError::PositionOverflow)?;
                 generated_col = 0;
             }
         }
 
-        if let Some(mapping) = buffer.last() {
-            if mapping.generated().line != generated_line {
+        if let Some(last) = last_decoded.filter(|_| self.insert_end_sentinel) {
+            if last.line != generated_line {
                 // There is only one scenario where the last mapping's line would differ from
                 // the final generated_line: when the last line has no mappings.
                 // Therefore, a mapping that points to the start of the last line
                 // needs to be inserted to mark the end of the map.
-                buffer.push(Mapping::new(generated_line, 0))
+                let position = Position::new(generated_line, 0);
+                let sentinel = keep(position);
+                if sentinel {
+                    push_mapping(
+                        buffer,
+                        Mapping::new(generated_line, 0),
+                        usize::MAX,
+                        usize::MAX,
+                    )?;
+                }
+                return Ok(DecodedEnd {
+                    position: Some(position),
+                    sentinel,
+                });
             }
         }
 
-        Ok(())
+        Ok(DecodedEnd {
+            position: last_decoded,
+            sentinel: false,
+        })
+    }
+}
+
+/// Pushes a mapping, growing the buffer fallibly so that an allocation failure on adversarial
+/// input is reported as [AllocationFailed](ParseError::AllocationFailed) instead of aborting.
+///
+/// The buffer holds at most `capacity` mappings, which is `max_mappings` plus the end sentinels.
+#[inline(always)]
+fn push_mapping(
+    buffer: &mut Vec<Mapping>,
+    mapping: Mapping,
+    capacity: usize,
+    max_mappings: usize,
+) -> ParseResult<()> {
+    if unlikely!(buffer.len() >= capacity) {
+        return Err(ParseError::TooManyMappings {
+            limit: max_mappings,
+        });
+    }
+    if unlikely!(buffer.len() == buffer.capacity()) {
+        try_reserve(buffer, buffer.len().max(256))?;
+    }
+    buffer.push(mapping);
+    Ok(())
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of reservations of the mappings buffer to succeed before failing, to test the
+    /// parsing on allocation failures.
+    static RESERVATIONS_BEFORE_FAILURE: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+}
+
+#[inline(always)]
+fn try_reserve(buffer: &mut Vec<Mapping>, additional: usize) -> ParseResult<()> {
+    #[cfg(test)]
+    if let Some(n) = RESERVATIONS_BEFORE_FAILURE.with(|n| n.get()) {
+        RESERVATIONS_BEFORE_FAILURE.with(|cell| cell.set(n.checked_sub(1)));
+        if n == 0 {
+            // a real error, which can't be constructed otherwise
+            buffer.try_reserve(usize::MAX)?;
+        }
+    }
+    buffer.try_reserve(additional)?;
+    Ok(())
+}
+
+/// Applies a relative generated column, which must be non-negative and fit in `u32`.
+#[inline(always)]
+fn add_generated_col(generated_col: u32, delta: i64) -> ParseResult<u32> {
+    if unlikely!(delta < 0) {
+        return Err(ParseError::MappingsUnordered);
+    }
+    let generated_col = generated_col as i64 + delta;
+    if unlikely!(generated_col > u32::MAX as i64) {
+        return Err(ParseError::PositionOverflow);
+    }
+    Ok(generated_col as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mappings, MappingsDecoder};
+    use crate::{Mapping, ParseError, ParseOptions, Position, ValidateError};
+
+    #[test]
+    #[cfg(all(feature = "builder", debug_assertions))]
+    fn test_ordering_report() {
+        use std::cmp::Ordering;
+
+        // SAFETY: the mappings are only inspected, never searched
+        let mappings = unsafe {
+            Mappings::new_unchecked(vec![
+                Mapping::new(0, 0),
+                Mapping::new(0, 5),
+                Mapping::new(0, 5).with_source(0, 0, 0),
+                Mapping::new(0, 2),
+                Mapping::new(1, 0),
+            ])
+        };
+        assert_eq!(
+            mappings.ordering_report(),
+            vec![
+                (0, Ordering::Less),
+                (1, Ordering::Equal),
+                (2, Ordering::Greater),
+                (3, Ordering::Less),
+            ]
+        );
+        let first_unordered = mappings
+            .ordering_report()
+            .into_iter()
+            .find(|(_, ord)| ord.is_gt())
+            .map(|(idx, _)| idx);
+        assert_eq!(first_unordered, Some(2));
+
+        assert!(Mappings::default().ordering_report().is_empty());
+    }
+
+    #[test]
+    fn test_by_line() {
+        let mappings = MappingsDecoder::new(";AAAA,CAAC;;;AACA;AACA,CAAC,CAAC;")
+            .items_count(1, 0)
+            .decode()
+            .unwrap();
+        let lines = mappings.by_line().collect::<Vec<_>>();
+        assert_eq!(
+            lines
+                .iter()
+                .map(|(line, m)| (*line, m.len()))
+                .collect::<Vec<_>>(),
+            vec![(1, 2), (4, 1), (5, 3), (6, 1)]
+        );
+        for (line, line_mappings) in lines {
+            assert_eq!(line_mappings, mappings.line_mappings(line));
+        }
+        assert_eq!(
+            mappings.by_line().map(|(_, m)| m.len()).sum::<usize>(),
+            mappings.len()
+        );
+
+        assert_eq!(Mappings::default().by_line().count(), 0);
+    }
+
+    #[test]
+    fn test_segment_counts_per_line() {
+        let mappings = MappingsDecoder::new(";AAAA,CAAC;;;AACA;AACA,CAAC,CAAC;")
+            .items_count(1, 0)
+            .decode()
+            .unwrap();
+        // the trailing `;` results in the end sentinel on line 6
+        assert_eq!(
+            mappings.segment_counts_per_line().collect::<Vec<_>>(),
+            vec![(1, 2), (4, 1), (5, 3), (6, 1)]
+        );
+        assert_eq!(
+            mappings
+                .segment_counts_per_line()
+                .map(|(_, count)| count)
+                .sum::<u32>() as usize,
+            mappings.len()
+        );
+        assert_eq!(Mappings::default().segment_counts_per_line().count(), 0);
+
+        // the lines are not materialized up to the last one
+        #[cfg(feature = "index-map")]
+        {
+            let mappings = MappingsDecoder::new("AAAA")
+                .state(u32::MAX, 0, 0, 0)
+                .items_count(1, 0)
+                .decode()
+                .unwrap();
+            assert_eq!(
+                mappings.segment_counts_per_line().collect::<Vec<_>>(),
+                vec![(u32::MAX, 1)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_trailing_separators() {
+        let decode = |s| {
+            MappingsDecoder::new(s)
+                .items_count(1, 0)
+                .decode()
+                .unwrap()
+                .0
+        };
+        let first = Mapping::new(0, 0).with_source(0, 0, 0);
+
+        // a trailing `;` starts an empty line, which is marked by an end-of-map mapping
+        assert_eq!(decode("AAAA;"), vec![first.clone(), Mapping::new(1, 0)]);
+        // a trailing `,` is an empty segment
+        assert_eq!(decode("AAAA,"), vec![first.clone()]);
+        assert_eq!(decode("AAAA,;"), vec![first, Mapping::new(1, 0)]);
+        assert_eq!(decode(";;;"), vec![]);
+        assert_eq!(decode(""), vec![]);
+    }
+
+    #[test]
+    fn test_decode_filtered_end_sentinel() {
+        let decode = |s, discarded: Position| {
+            let mut mappings = Mappings::default();
+            let end = MappingsDecoder::new(s)
+                .items_count(1, 0)
+                .decode_filtered_into(&mut mappings, |pos| pos != discarded)
+                .unwrap();
+            (mappings.0, end.position)
+        };
+        let first = Mapping::new(0, 0).with_source(0, 0, 0);
+
+        // the last line has a discarded mapping, so it isn't empty
+        assert_eq!(
+            decode("AAAA;CAAC", Position::new(1, 1)),
+            (vec![first.clone()], Some(Position::new(1, 1)))
+        );
+        assert_eq!(
+            decode("AAAA;CAAC;", Position::new(1, 1)),
+            (
+                vec![first.clone(), Mapping::new(2, 0)],
+                Some(Position::new(2, 0))
+            )
+        );
+        // the sentinel is subject to `keep` as well
+        assert_eq!(
+            decode("AAAA;", Position::new(1, 0)),
+            (vec![first], Some(Position::new(1, 0)))
+        );
+        assert_eq!(decode(";;", Position::new(0, 0)), (vec![], None));
+    }
+
+    #[test]
+    fn test_encoded_len() {
+        for source in [
+            "",
+            "AAAA",
+            ";;;AAAA",
+            "AAAA,IAAIA;;E,GCEJC;CDFA",
+            "A,gBAAkB,C;;A",
+        ] {
+            let mappings = MappingsDecoder::new(source)
+                .items_count(3, 3)
+                .decode()
+                .unwrap();
+            assert_eq!(mappings.to_encoded_string(), source);
+            assert_eq!(mappings.encoded_len(), source.len());
+        }
+    }
+
+    #[test]
+    fn test_rfind_mapping() {
+        let mappings = MappingsDecoder::new("A,E;;C,G")
+            .items_count(0, 0)
+            .decode()
+            .unwrap();
+        let rfind = |line, column| mappings.rfind_mapping((line, column));
+        assert_eq!(rfind(0, 0), Some(Mapping::new(0, 0)));
+        assert_eq!(rfind(0, 1), Some(Mapping::new(0, 2)));
+        assert_eq!(rfind(0, 2), Some(Mapping::new(0, 2)));
+        // the closest following mapping is on a later line
+        assert_eq!(rfind(0, 3), Some(Mapping::new(2, 1)));
+        assert_eq!(rfind(1, 100), Some(Mapping::new(2, 1)));
+        assert_eq!(rfind(2, 2), Some(Mapping::new(2, 4)));
+        assert_eq!(rfind(2, 4), Some(Mapping::new(2, 4)));
+        assert_eq!(rfind(2, 5), None);
+
+        // symmetric to find_mapping on exact matches
+        for mapping in mappings.iter() {
+            let pos = mapping.generated();
+            assert_eq!(mappings.rfind_mapping(pos), mappings.find_mapping(pos));
+        }
+
+        let empty = MappingsDecoder::new("").decode().unwrap();
+        assert_eq!(empty.rfind_mapping((0, 0)), None);
+    }
+
+    #[test]
+    fn test_decode_growth() {
+        let segments = 100_000;
+        let source = format!("AAAA{}", ",CAAC".repeat(segments - 1));
+        let mappings = MappingsDecoder::new(&source)
+            .items_count(1, 0)
+            .decode()
+            .unwrap();
+        assert_eq!(mappings.len(), segments);
+        assert_eq!(
+            mappings.last(),
+            Some(&Mapping::new(0, segments as u32 - 1).with_source(0, 0, segments as u32 - 1))
+        );
+    }
+
+    #[test]
+    fn test_allocation_failed() {
+        use super::RESERVATIONS_BEFORE_FAILURE;
+        use crate::BorrowedSourceMap;
+
+        // fails the initial reservation, then the growth past 256 mappings
+        let mappings = vec!["A"; 300].join(",");
+        let json = format!(r#"{{"version":3,"mappings":"{mappings}"}}"#);
+        for reservations in [0, 1] {
+            RESERVATIONS_BEFORE_FAILURE.with(|n| n.set(Some(reservations)));
+            assert!(matches!(
+                BorrowedSourceMap::from_slice(&mut json.clone().into_bytes()),
+                Err(ParseError::AllocationFailed(..))
+            ));
+            RESERVATIONS_BEFORE_FAILURE.with(|n| n.set(Some(reservations)));
+            assert!(matches!(
+                BorrowedSourceMap::from_slice_filtered(&mut json.clone().into_bytes(), |_| true),
+                Err(ParseError::AllocationFailed(..))
+            ));
+        }
+
+        RESERVATIONS_BEFORE_FAILURE.with(|n| n.set(None));
+        let mut buf = json.into_bytes();
+        let sm = BorrowedSourceMap::from_slice(&mut buf).unwrap();
+        assert_eq!(sm.mappings().len(), 300);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        for source in [
+            "",
+            "AAAA;;;AAAA",
+            "AAAA,IAAIA;;E,GCEJC;CDFA",
+            "A,gBAAkB,C;;A",
+        ] {
+            let mappings = MappingsDecoder::new(source)
+                .items_count(3, 3)
+                .decode()
+                .unwrap();
+            let bytes = mappings.to_bytes();
+            assert_eq!(bytes.len(), 16 + mappings.len() * 24);
+            assert_eq!(Mappings::from_bytes(&bytes).unwrap(), mappings);
+        }
+    }
+
+    #[test]
+    fn test_binary_invalid() {
+        let mappings = MappingsDecoder::new("AAAAA,CAAC;A")
+            .items_count(1, 1)
+            .decode()
+            .unwrap();
+        let bytes = mappings.to_bytes();
+
+        let from_bytes = |bytes: &[u8]| Mappings::from_bytes(bytes).unwrap_err();
+        // truncated
+        for len in [0, 3, 15, 16, bytes.len() - 1] {
+            assert!(
+                matches!(from_bytes(&bytes[..len]), ParseError::InvalidBinary(..)),
+                "{}",
+                len
+            );
+        }
+        // garbage
+        assert!(matches!(
+            from_bytes(b"garbage garbage garbage"),
+            ParseError::InvalidBinary(..)
+        ));
+        // bad version
+        let mut invalid = bytes.clone();
+        invalid[4] = 2;
+        assert!(matches!(
+            from_bytes(&invalid),
+            ParseError::InvalidBinary(..)
+        ));
+        // huge count
+        let mut invalid = bytes.clone();
+        invalid[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            from_bytes(&invalid),
+            ParseError::InvalidBinary(..)
+        ));
+        // name without source in the last mapping
+        let mut invalid = bytes.clone();
+        let len = invalid.len();
+        invalid[len - 4..].copy_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(
+            from_bytes(&invalid),
+            ParseError::InvalidBinary(..)
+        ));
+        // unordered: move the second mapping to line 2, after the last one on line 1
+        let mut invalid = bytes.clone();
+        invalid[16 + 24..16 + 28].copy_from_slice(&2u32.to_le_bytes());
+        assert!(matches!(
+            from_bytes(&invalid),
+            ParseError::MappingsUnordered
+        ));
+    }
+
+    #[test]
+    fn test_decode_leading_empty_lines() {
+        let mappings = MappingsDecoder::new(";;;AAAA")
+            .items_count(1, 0)
+            .decode()
+            .unwrap();
+        assert_eq!(mappings.0, vec![Mapping::new(3, 0).with_source(0, 0, 0)]);
+
+        assert_eq!(
+            mappings.find_mapping((3, 0)),
+            Some(Mapping::new(3, 0).with_source(0, 0, 0))
+        );
+        assert_eq!(
+            mappings.find_mapping((3, 10)),
+            Some(Mapping::new(3, 0).with_source(0, 0, 0))
+        );
+        assert_eq!(mappings.find_mapping((2, 0)), None);
+    }
+
+    #[test]
+    fn test_decode_segment_field_count() {
+        let decode = |s| MappingsDecoder::new(s).items_count(1, 1).decode();
+
+        assert!(matches!(
+            decode("AA"),
+            Err(ParseError::MappingSegmentFieldCount { found: 2 })
+        ));
+        assert!(matches!(
+            decode("AAAA,AAA"),
+            Err(ParseError::MappingSegmentFieldCount { found: 3 })
+        ));
+        assert!(matches!(
+            decode("AAAAAA"),
+            Err(ParseError::MappingMalformed(..))
+        ));
+    }
+
+    #[test]
+    fn test_rebase_names() {
+        let mut mappings = MappingsDecoder::new("AAAAA,CAAAC;AAAA")
+            .items_count(1, 2)
+            .options(&ParseOptions {
+                insert_end_sentinel: false,
+                ..Default::default()
+            })
+            .decode()
+            .unwrap();
+        mappings.rebase_names(3).unwrap();
+        assert_eq!(
+            mappings.0,
+            vec![
+                Mapping::new(0, 0).with_source(0, 0, 0).with_name(3),
+                Mapping::new(0, 1).with_source(0, 0, 0).with_name(4),
+                Mapping::new(1, 0).with_source(0, 0, 0),
+            ]
+        );
+
+        let before = mappings.clone();
+        assert!(matches!(
+            mappings.rebase_names(u32::MAX - 4),
+            Err(ValidateError::UnknownNameReference(4))
+        ));
+        assert_eq!(mappings, before);
+    }
+
+    #[test]
+    fn test_extend_offset() {
+        let mut mappings = MappingsDecoder::new("AAAA;CAAC")
+            .items_count(1, 0)
+            .decode()
+            .unwrap();
+        let other = MappingsDecoder::new("AAAAA;;ECAA")
+            .items_count(2, 1)
+            .decode()
+            .unwrap();
+
+        mappings.extend_offset(&other, 2, 1, 3).unwrap();
+        assert_eq!(
+            mappings.0,
+            vec![
+                Mapping::new(0, 0).with_source(0, 0, 0),
+                Mapping::new(1, 1).with_source(0, 0, 1),
+                Mapping::new(2, 0).with_source(1, 0, 0).with_name(3),
+                Mapping::new(4, 2).with_source(2, 0, 0),
+            ]
+        );
+
+        // the shifted mappings must follow the existing ones
+        assert!(matches!(
+            mappings.extend_offset(&other, 3, 0, 0),
+            Err(ValidateError::MappingsUnordered)
+        ));
+        // the rebased ids must not overflow, nor become the missing id
+        let before = mappings.clone();
+        assert!(matches!(
+            mappings.extend_offset(&other, 9, u32::MAX - 1, 0),
+            Err(ValidateError::UnknownSourceReference(1))
+        ));
+        assert!(matches!(
+            mappings.extend_offset(&other, 9, 0, u32::MAX),
+            Err(ValidateError::UnknownNameReference(0))
+        ));
+        assert_eq!(mappings, before);
+        assert!(matches!(
+            mappings.extend_offset(&other, u32::MAX - 1, 0, 0),
+            Err(ValidateError::PositionOverflow)
+        ));
+        assert_eq!(mappings.len(), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_check_encodable() {
+        use crate::Position;
+
+        let max = i32::MAX as u32;
+        let encodable = Mappings::new(vec![
+            Mapping::new(0, max)
+                .with_source(max, max, max)
+                .with_name(max),
+            // a delta of `-i32::MAX`
+            Mapping::new(1, 0).with_source(0, 0, 0).with_name(0),
+            // the generated column starts over on each line
+            Mapping::new(2, max),
+        ]);
+        encodable.check_encodable().unwrap();
+        assert!(MappingsDecoder::new(&encodable.to_encoded_string())
+            .items_count(u32::MAX, u32::MAX)
+            .decode()
+            .is_ok());
+
+        for (mapping, position) in [
+            (Mapping::new(0, max + 1), Position::new(0, max + 1)),
+            (
+                Mapping::new(3, 0).with_source(max + 1, 0, 0),
+                Position::new(3, 0),
+            ),
+            (
+                Mapping::new(3, 0).with_source(0, u32::MAX, 0),
+                Position::new(3, 0),
+            ),
+            (
+                Mapping::new(3, 0).with_source(0, 0, 0).with_name(max + 1),
+                Position::new(3, 0),
+            ),
+        ] {
+            let mappings = Mappings::new(vec![Mapping::new(0, 0).with_source(0, 0, 0), mapping]);
+            assert!(matches!(
+                mappings.check_encodable(),
+                Err(ValidateError::DeltaOverflow(pos)) if pos == position
+            ));
+        }
+
+        // lines can't go backward, built directly since `Mappings::new` sorts them
+        let unordered = Mappings(vec![Mapping::new(1, 0), Mapping::new(0, 0)]);
+        assert!(matches!(
+            unordered.check_encodable(),
+            Err(ValidateError::MappingsUnordered)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_mappings_builder() {
+        use super::MappingsBuilder;
+
+        let mut builder = MappingsBuilder::new();
+        builder
+            .push_generated(0)
+            .unwrap()
+            .push_source(0, 0, 0)
+            .unwrap();
+        builder
+            .push_generated(4)
+            .unwrap()
+            .push_source(0, 0, 4)
+            .unwrap()
+            .push_name(0)
+            .unwrap();
+        builder.new_line().unwrap();
+        builder.new_line().unwrap();
+        builder.push_generated(2).unwrap();
+        builder
+            .push_generated(3)
+            .unwrap()
+            .push_source(1, 2, -4)
+            .unwrap()
+            .push_name(1)
+            .unwrap();
+        builder.new_line().unwrap();
+        builder
+            .push_generated(1)
+            .unwrap()
+            .push_source(-1, -2, 0)
+            .unwrap();
+
+        let decoded = MappingsDecoder::new("AAAA,IAAIA;;E,GCEJC;CDFA")
+            .items_count(2, 2)
+            .decode()
+            .unwrap();
+        assert_eq!(&*builder.build(), &*decoded);
+    }
+
+    #[test]
+    #[cfg(feature = "builder")]
+    fn test_mappings_builder_out_of_range() {
+        use super::MappingsBuilder;
+
+        let mut builder = MappingsBuilder::new();
+        assert!(matches!(
+            builder.push_generated(-1),
+            Err(ValidateError::MappingsUnordered)
+        ));
+        builder.push_generated(0).unwrap();
+        assert!(matches!(
+            builder.push_source(-1, 0, 0),
+            Err(ValidateError::StateOutOfRange)
+        ));
+        assert!(matches!(
+            builder.push_source(u32::MAX as i64, 0, 0),
+            Err(ValidateError::StateOutOfRange)
+        ));
+        assert!(matches!(
+            builder.push_source(0, 0, -1),
+            Err(ValidateError::StateOutOfRange)
+        ));
+        // the failed deltas leave the state unchanged
+        builder.push_source(0, 0, 0).unwrap();
+        assert!(matches!(
+            builder.push_name(-1),
+            Err(ValidateError::StateOutOfRange)
+        ));
+        builder.push_name(0).unwrap();
+        assert!(matches!(
+            builder.push_generated(u32::MAX as i64 + 1),
+            Err(ValidateError::PositionOverflow)
+        ));
+        assert!(matches!(
+            builder.push_generated(i64::MAX),
+            Err(ValidateError::PositionOverflow)
+        ));
+        assert_eq!(
+            &*builder.build(),
+            &[Mapping::new(0, 0).with_source(0, 0, 0).with_name(0)]
+        );
     }
 }
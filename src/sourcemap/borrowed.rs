@@ -1,15 +1,17 @@
-use crate::finder::MappingFinder;
+use crate::finder::{CachingFinder, LookupTable, MappingFinder};
 use crate::hint::unlikely;
-use crate::mapping::{Mapping, Position};
+use crate::mapping::{Mapping, Position, SourceInfo};
 use crate::mappings::{ItemsCount, Mappings, MappingsDecoder};
-use crate::sourcemap::raw::RawSourceMap;
-use crate::{ParseError, ParseResult, ValidateError, ValidateResult};
+use crate::sourcemap::raw::{RawSource, RawSourceMap};
+use crate::sourcemap::{CommentStyle, CompatMode, ParseOptions, WriteOptions};
+use crate::vlq::{encode_base64, VlqAlphabet};
+use crate::{LoadError, ParseError, ParseResult, ParseWarning, ValidateError, ValidateResult};
 use simd_json_derive::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::io;
 use std::io::Write;
-use std::iter::repeat_with;
 
 /// `BorrowedSourceMap` is a source map containing borrowed or owned strings.
 ///
@@ -23,6 +25,8 @@ use std::iter::repeat_with;
 /// You can create a `BorrowedSourceMap` using the following methods:
 /// - [`BorrowedSourceMap::from_str`]
 /// - [`BorrowedSourceMap::from_slice`]
+/// - [`BorrowedSourceMap::from_slice_with_warnings`]
+/// - [`BorrowedSourceMap::from_slice_with_options`]
 ///
 /// These methods take **mutable** references as parameters because they may modify
 /// the data in place.
@@ -49,13 +53,19 @@ use std::iter::repeat_with;
 /// Unsafe methods allow for more extensive modifications to the source map.
 ///
 /// Note: After making changes, call [`validate`](BorrowedSourceMap::validate) to ensure that
-/// the source map remains valid and does not contain broken data.
+/// the source map remains valid and does not contain broken data, or the cheaper
+/// [`validate_references_only`](BorrowedSourceMap::validate_references_only) if `mappings`
+/// are untouched.
 ///
 /// ## Finding Mappings
 ///
 /// To find mappings corresponding to specific positions, you can use:
 /// - [`find_mapping`](BorrowedSourceMap::find_mapping)
+/// - [`rfind_mapping`](BorrowedSourceMap::rfind_mapping)
+/// - [`surrounding_mappings`](BorrowedSourceMap::surrounding_mappings)
 /// - [`finder`](BorrowedSourceMap::finder)
+/// - [`caching_finder`](BorrowedSourceMap::caching_finder)
+/// - [`lookup_table`](BorrowedSourceMap::lookup_table)
 ///
 /// ## Output
 ///
@@ -63,7 +73,15 @@ use std::iter::repeat_with;
 /// - [`write`](BorrowedSourceMap::write)
 /// - [`to_vec`](BorrowedSourceMap::to_vec)
 /// - [`to_string`](BorrowedSourceMap::to_string)
-#[derive(Clone)]
+///
+/// For debugging, [`to_debug_string`](BorrowedSourceMap::to_debug_string) renders a readable dump.
+///
+/// ## Comparison
+///
+/// `BorrowedSourceMap` implements `PartialEq` structurally, comparing all the fields one by one.
+/// Two maps resolving every position to the same locations may still differ, for example when
+/// their sources are in different orders.
+#[derive(Clone, Eq, PartialEq)]
 pub struct BorrowedSourceMap<'a> {
     pub(crate) file: Option<Cow<'a, str>>,
     pub(crate) mappings: Mappings,
@@ -77,6 +95,39 @@ pub struct BorrowedSourceMap<'a> {
 
 impl Debug for BorrowedSourceMap<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_debug(f, false)
+    }
+}
+
+impl BorrowedSourceMap<'_> {
+    /// Renders the source map into a multi-line textual dump for debugging.
+    ///
+    /// The layout is the same as the `Debug` output, except that each mapping with source
+    /// information is followed by the path of its source.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(
+    ///     br#"{"version":3,"sources":["a.js"],"names":["foo"],"mappings":"AAAA,EAACA"}"#.to_vec(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     sm.to_debug_string(),
+    ///     "SourceMap\n  sources:\n    0: a.js\n  names:\n    0: foo\n  mappings:\n    0:0 -> 0:0:0 [a.js], 0:2 -> 0:0:1 (0) [a.js]"
+    /// );
+    /// ```
+    pub fn to_debug_string(&self) -> String {
+        let mut s = String::new();
+        // writing into a String never fails
+        let _ = self.write_debug(&mut s, true);
+        s
+    }
+
+    fn write_debug<W>(&self, f: &mut W, with_source_path: bool) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+    {
         f.write_str("SourceMap\n")?;
         f.write_str("  sources:\n")?;
         for (idx, source) in self.sources.iter().enumerate() {
@@ -88,16 +139,28 @@ impl Debug for BorrowedSourceMap<'_> {
             writeln!(f, "    {idx}: {name}")?;
         }
         f.write_str("  mappings:\n")?;
+
+        let write_mapping = |f: &mut W, mapping: &Mapping| {
+            write!(f, "{:?}", mapping)?;
+            if with_source_path {
+                if let Some(source_id) = mapping.source_id() {
+                    write!(f, " [{}]", self.source_at(source_id).unwrap_or(""))?;
+                }
+            }
+            Ok(())
+        };
+
         if !self.mappings.is_empty() {
             let mut last_mapping = &self.mappings[0];
-            write!(f, "    {:?}", last_mapping)?;
+            f.write_str("    ")?;
+            write_mapping(f, last_mapping)?;
             for mapping in self.mappings.iter().skip(1) {
-                if mapping.generated().line != last_mapping.generated().line {
+                if !mapping.same_generated_line(last_mapping) {
                     f.write_str("\n    ")?;
                 } else {
                     f.write_str(", ")?;
                 }
-                write!(f, "{:?}", mapping)?;
+                write_mapping(f, mapping)?;
                 last_mapping = mapping;
             }
         }
@@ -105,6 +168,27 @@ impl Debug for BorrowedSourceMap<'_> {
     }
 }
 
+impl BorrowedSourceMap<'_> {
+    /// The version of the source map format supported, which is the only accepted value of the
+    /// `version` field.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let json = format!(r#"{{"version":{},"mappings":""}}"#, SourceMap::VERSION);
+    /// assert!(SourceMap::from(json.into_bytes()).is_ok());
+    /// ```
+    pub const VERSION: u32 = 3;
+
+    /// Returns `true` if index maps are supported, i.e. the feature `index-map` is enabled.
+    ///
+    /// Otherwise, the `sections` of index maps are ignored when parsing.
+    #[inline]
+    pub const fn supports_index_map() -> bool {
+        cfg!(feature = "index-map")
+    }
+}
+
 impl<'a> BorrowedSourceMap<'a> {
     /// Finds the mapping for a given generated position.
     ///
@@ -129,6 +213,86 @@ impl<'a> BorrowedSourceMap<'a> {
         self.mappings.find_mapping(pos)
     }
 
+    /// Finds the mapping for a given generated position, searching forward.
+    ///
+    /// It is symmetric to [find_mapping](Self::find_mapping): if an exact match is not found,
+    /// this method returns the closest following mapping.
+    /// If there are no following mappings, it returns `None`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Mapping, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A,E;C"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.rfind_mapping((0, 1)), Some(Mapping::new(0, 2)));
+    /// assert_eq!(sm.rfind_mapping((1, 2)), None);
+    /// ```
+    pub fn rfind_mapping<P>(&self, pos: P) -> Option<Mapping>
+    where
+        P: Into<Position>,
+    {
+        self.mappings.rfind_mapping(pos)
+    }
+
+    /// Finds the mappings around a given generated position: the closest one at or before it,
+    /// and the closest one strictly after it.
+    ///
+    /// The first one is the same as the result of [find_mapping](Self::find_mapping).
+    /// It's useful to interpolate positions in regions without mappings.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Mapping, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"mappings":"A;;;E"}"#.to_vec()).unwrap();
+    /// assert_eq!(
+    ///     sm.surrounding_mappings((1, 0)),
+    ///     (Some(Mapping::new(0, 0)), Some(Mapping::new(3, 2)))
+    /// );
+    /// ```
+    pub fn surrounding_mappings<P>(&self, pos: P) -> (Option<Mapping>, Option<Mapping>)
+    where
+        P: Into<Position>,
+    {
+        self.mappings.surrounding_mappings(pos)
+    }
+
+    /// Finds the span in the original source covered by the generated token at a given position.
+    ///
+    /// The span starts at the source position of the mapping found like
+    /// [find_mapping](Self::find_mapping), and ends at the source position of the next mapping
+    /// in the same source (exclusive), skipping the mappings without source information or in
+    /// other sources.
+    ///
+    /// Returns `None` if the found mapping has no source information, or there is no next
+    /// mapping in the same source. Note that source positions are not monotonic across
+    /// mappings, e.g. when a minifier reorders code, so `None` is also returned if the end
+    /// precedes the start.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{SourceInfo, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,EAAG"}"#.to_vec()).unwrap();
+    /// let (start, end) = sm.original_span((0, 1)).unwrap();
+    /// assert_eq!(start, SourceInfo::new(0, (0, 0).into()));
+    /// assert_eq!(end, SourceInfo::new(0, (0, 3).into()));
+    /// ```
+    pub fn original_span<P>(&self, pos: P) -> Option<(SourceInfo, SourceInfo)>
+    where
+        P: Into<Position>,
+    {
+        self.mappings.original_span(pos)
+    }
+
+    /// Checks whether the generated position is within the range covered by the mappings,
+    /// i.e. between the first and the last mapping (both inclusive).
+    ///
+    /// This distinguishes positions before or after all mappings from positions in unmapped gaps.
+    pub fn contains_position<P>(&self, pos: P) -> bool
+    where
+        P: Into<Position>,
+    {
+        self.mappings.contains_position(pos)
+    }
+
     /// Creates a `MappingFinder` for the source map.
     ///
     /// This stateful finder is highly efficient for frequent mapping findings,
@@ -148,6 +312,146 @@ impl<'a> BorrowedSourceMap<'a> {
         self.mappings.finder()
     }
 
+    /// Creates a `CachingFinder` for the source map, which caches the mappings range of at most
+    /// `capacity` recently queried generated lines.
+    ///
+    /// It is efficient for repeated findings on the same few lines in arbitrary order.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{BorrowedSourceMap, Position};
+    /// # let mut buf = r#"{"version": 3}"#.as_bytes().to_vec();
+    /// let source_map = BorrowedSourceMap::from_slice(&mut buf).unwrap();
+    /// let finder = source_map.caching_finder(8);
+    /// finder.find_mapping((1, 6));
+    /// finder.find_mapping((1, 2));
+    /// ```
+    pub fn caching_finder(&self, capacity: usize) -> CachingFinder {
+        self.mappings.caching_finder(capacity)
+    }
+
+    /// Creates a [LookupTable] for the source map, which holds the mappings grouped by
+    /// generated line for finding them without searching over the whole mappings.
+    ///
+    /// The table is owned and does not borrow the source map.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{BorrowedSourceMap, Position};
+    /// # let mut buf = r#"{"version": 3}"#.as_bytes().to_vec();
+    /// let source_map = BorrowedSourceMap::from_slice(&mut buf).unwrap();
+    /// let table = source_map.lookup_table();
+    /// table.lookup(1, 6);
+    /// ```
+    pub fn lookup_table(&self) -> LookupTable {
+        self.mappings.lookup_table()
+    }
+
+    /// Returns the distinct source positions referenced by the mappings on generated lines
+    /// from `start_line` (inclusive) to `end_line` (exclusive), sorted by source id and position.
+    ///
+    /// This is useful to find out which parts of the original sources produced a part of the
+    /// generated file.
+    pub fn sources_in_generated_range(&self, start_line: u32, end_line: u32) -> Vec<SourceInfo> {
+        if start_line >= end_line {
+            return Vec::new();
+        }
+        let mut result = self.mappings[self.mappings.lines_range(start_line, end_line - 1)]
+            .iter()
+            .filter_map(Mapping::source_info)
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
+    /// Returns `true` if there is at least one mapping with a source, i.e. the map carries some
+    /// positional data.
+    ///
+    /// Unlike `mappings().is_empty()`, a map with only generated-only mappings, such as the end
+    /// sentinel, is considered as having no mappings.
+    #[inline]
+    pub fn has_mappings(&self) -> bool {
+        self.mappings.iter().any(Mapping::has_source)
+    }
+
+    /// Returns whether the `file` is also listed in the `sources`, which usually means the
+    /// generated file is accidentally mapped to itself.
+    ///
+    /// The paths are compared as is, without resolving the `source_root`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"file":"out.js","sources":["a.js","out.js"],"mappings":""}"#.to_vec()).unwrap();
+    /// assert!(sm.file_in_sources());
+    /// ```
+    pub fn file_in_sources(&self) -> bool {
+        self.file.as_deref().is_some_and(|file| {
+            self.sources
+                .iter()
+                .any(|source| source.as_deref() == Some(file))
+        })
+    }
+
+    /// Returns the number of generated lines covered by the mappings, i.e. the last generated
+    /// line plus one, or `0` if there is no mapping. It saturates at `u32::MAX`, if the last
+    /// generated line is `u32::MAX`.
+    ///
+    /// Note that it's not the number of mappings, nor necessarily the number of lines in the
+    /// generated file, since trailing lines may have no mappings.
+    #[inline]
+    pub fn generated_line_count(&self) -> u32 {
+        self.mappings
+            .last()
+            .map_or(0, |m| m.generated().line.saturating_add(1))
+    }
+
+    /// Returns the fraction of the generated lines with at least one mapping with source
+    /// information, over the [generated_line_count](Self::generated_line_count), e.g. as a
+    /// quality gate for builds. Returns `0.0` if there is no mapping.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;;A;AACA"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.mapped_coverage(), 0.5);
+    /// ```
+    pub fn mapped_coverage(&self) -> f32 {
+        // unlike `generated_line_count`, it can't saturate
+        let Some(total) = self.mappings.last().map(|m| m.generated().line as u64 + 1) else {
+            return 0.0;
+        };
+        let mapped = self
+            .mappings
+            .by_line()
+            .filter(|(_, mappings)| mappings.iter().any(Mapping::has_source))
+            .count();
+        (mapped as f64 / total as f64) as f32
+    }
+
+    /// Returns the generated positions of all the mappings from the given line of the source,
+    /// in the generated order.
+    ///
+    /// This scans all the mappings, which takes O(n) time.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Position, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,EAAC;AACA,EAAC"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.generated_for_source_line(0, 1), vec![Position::new(1, 0), Position::new(1, 2)]);
+    /// ```
+    pub fn generated_for_source_line(&self, source_id: u32, source_line: u32) -> Vec<Position> {
+        self.mappings
+            .iter()
+            .filter(|m| {
+                m.source_info()
+                    .is_some_and(|s| s.id == source_id && s.position.line == source_line)
+            })
+            .map(Mapping::generated)
+            .collect()
+    }
+
     /// Validates the source map.
     pub fn validate(&self) -> ValidateResult<()> {
         let sources_len = self.sources.len() as u32;
@@ -170,6 +474,45 @@ impl<'a> BorrowedSourceMap<'a> {
 
         Ok(())
     }
+
+    /// Validates the source map like [validate](Self::validate), but skips checking the order of
+    /// `mappings`.
+    ///
+    /// This is a cheaper check after modifying the `sources`, `sources_content` or `names`, e.g.
+    /// via [sources_mut2](Self::sources_mut2) or [names_mut2](Self::names_mut2), which can break
+    /// the references from `mappings` but not their order.
+    pub fn validate_references_only(&self) -> ValidateResult<()> {
+        let sources_len = self.sources.len() as u32;
+        let sources_content_len = self.sources_content.len() as u32;
+        let names_len = self.names.len() as u32;
+
+        if sources_content_len != sources_len {
+            return Err(ValidateError::MismatchSourcesContent {
+                sources_len,
+                sources_content_len,
+            });
+        }
+
+        self.mappings
+            .validate_references(ItemsCount::new(sources_len, names_len))
+    }
+
+    /// Validates that each entry of the `ignore_list` references a source that has content.
+    ///
+    /// This is not part of [validate](Self::validate), since ignoring a source without content
+    /// is usually meaningless but harmless.
+    #[cfg(feature = "ignore_list")]
+    pub fn validate_ignore_list(&self) -> ValidateResult<()> {
+        for &source_id in &self.ignore_list {
+            match self.sources_content.get(source_id as usize) {
+                None => return Err(ValidateError::UnknownSourceReference(source_id)),
+                Some(None) => return Err(ValidateError::IgnoredSourceWithoutContent(source_id)),
+                Some(Some(_)) => {}
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a> BorrowedSourceMap<'a> {
@@ -183,6 +526,16 @@ impl<'a> BorrowedSourceMap<'a> {
         &mut self.file
     }
 
+    #[inline]
+    pub fn source_root(&self) -> &Option<Cow<'a, str>> {
+        &self.source_root
+    }
+
+    #[inline]
+    pub fn source_root_mut(&mut self) -> &mut Option<Cow<'a, str>> {
+        &mut self.source_root
+    }
+
     #[inline]
     pub fn mappings(&self) -> &Mappings {
         &self.mappings
@@ -193,6 +546,25 @@ impl<'a> BorrowedSourceMap<'a> {
         &mut self.mappings
     }
 
+    /// Rewrites the mappings into a canonical form, so that equivalent maps are encoded to the
+    /// same bytes by [to_vec](Self::to_vec), e.g. for content-addressable caching.
+    ///
+    /// The mappings are [sorted](Mappings::sort) by their full order, and then the redundant ones
+    /// are removed by [compact](Mappings::compact). The encoding itself is already deterministic
+    /// and minimal, e.g. the shortest VLQ is used and the name-less segments have 4 fields.
+    /// Note that `sources` and `names` are left as is.
+    pub fn canonicalize(&mut self) {
+        self.mappings.sort();
+        self.mappings.compact();
+    }
+
+    /// Moves the mappings out, leaving empty mappings in their place, which avoids a deep copy
+    /// when only the mappings are needed.
+    #[inline]
+    pub fn take_mappings(&mut self) -> Mappings {
+        std::mem::take(&mut self.mappings)
+    }
+
     #[inline]
     pub fn name_at(&self, id: u32) -> Option<&str> {
         self.names.get(id as usize).map(|name| name.as_ref())
@@ -244,6 +616,16 @@ impl<'a> BorrowedSourceMap<'a> {
         &mut self.sources_content
     }
 
+    /// Computes the SHA-256 digest of the content of the source, e.g. to check it against a
+    /// build manifest. Returns `None` if the source has no content.
+    #[cfg(feature = "digest")]
+    pub fn content_digest(&self, source_id: u32) -> Option<[u8; 32]> {
+        use sha2::{Digest, Sha256};
+
+        let content = self.source_content_at(source_id)?;
+        Some(Sha256::digest(content.as_bytes()).into())
+    }
+
     #[inline]
     #[cfg(feature = "ignore_list")]
     pub fn ignore_list(&self) -> &[u32] {
@@ -259,6 +641,169 @@ impl<'a> BorrowedSourceMap<'a> {
     }
 }
 
+impl<'a> BorrowedSourceMap<'a> {
+    /// Applies a transform to each source in place, skipping `None` sources.
+    ///
+    /// If the transform returns the input string itself as borrowed, the source is kept as is,
+    /// so a source borrowed from the input JSON remains borrowed.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// # use std::borrow::Cow;
+    /// let mut sm = SourceMap::from(br#"{"version":3,"sources":["src\\a.js"],"mappings":""}"#.to_vec()).unwrap();
+    /// sm.map_sources(|source| {
+    ///     if source.contains('\\') {
+    ///         Cow::Owned(source.replace('\\', "/"))
+    ///     } else {
+    ///         Cow::Borrowed(source)
+    ///     }
+    /// });
+    /// assert_eq!(sm.source_at(0), Some("src/a.js"));
+    /// ```
+    pub fn map_sources<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str) -> Cow<'_, str>,
+    {
+        for source in self.sources.iter_mut().flatten() {
+            map_cow(source, &mut f);
+        }
+    }
+
+    /// Applies a transform to each name in place.
+    ///
+    /// See [map_sources](Self::map_sources).
+    pub fn map_names<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str) -> Cow<'_, str>,
+    {
+        for name in self.names.iter_mut() {
+            map_cow(name, &mut f);
+        }
+    }
+
+    /// Fills the missing `sources_content` entries with the content of the same sources in
+    /// `other`, e.g. a sibling map generated with contents.
+    ///
+    /// Sources are matched by their path string. Entries which already have content are kept,
+    /// and sources without content in `other` are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let mut sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":""}"#.to_vec()).unwrap();
+    /// let other = SourceMap::from(br#"{"version":3,"sources":["b.js","a.js"],"sourcesContent":[null,"let a"],"mappings":""}"#.to_vec()).unwrap();
+    /// sm.fill_content_from(&other);
+    /// assert_eq!(sm.source_content_at(0), Some("let a"));
+    /// ```
+    pub fn fill_content_from(&mut self, other: &BorrowedSourceMap) {
+        let contents = other
+            .sources
+            .iter()
+            .zip(&other.sources_content)
+            .filter_map(|(source, content)| Some((source.as_deref()?, content.as_deref()?)))
+            .collect::<HashMap<_, _>>();
+        if contents.is_empty() {
+            return;
+        }
+
+        for (source, content) in self.sources.iter().zip(self.sources_content.iter_mut()) {
+            if content.is_some() {
+                continue;
+            }
+            if let Some(&found) = source.as_deref().and_then(|s| contents.get(s)) {
+                *content = Some(Cow::Owned(found.to_owned()));
+            }
+        }
+    }
+
+    /// Sorts the `sources` lexicographically by path, along with their `sources_content`, and
+    /// updates the source ids referenced in the `mappings` and `ignore_list` accordingly.
+    ///
+    /// Sources without a path are placed first. Builds discovering the same sources in
+    /// different orders produce identical maps after sorting.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let mut sm = SourceMap::from(br#"{"version":3,"sources":["b.js","a.js"],"mappings":"AAAA,CCAC"}"#.to_vec()).unwrap();
+    /// sm.sort_sources();
+    /// assert_eq!(sm.source_at(0), Some("a.js"));
+    /// assert_eq!(sm.find_mapping((0, 1)).unwrap().source_id(), Some(0));
+    /// ```
+    pub fn sort_sources(&mut self) {
+        let mut order = (0..self.sources.len() as u32).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| self.sources[a as usize].cmp(&self.sources[b as usize]));
+        if order.iter().enumerate().all(|(idx, &id)| idx as u32 == id) {
+            return;
+        }
+
+        // new_ids[old id] = new id
+        let mut new_ids = vec![0; order.len()];
+        for (new_id, &old_id) in order.iter().enumerate() {
+            new_ids[old_id as usize] = new_id as u32;
+        }
+
+        self.sources = reorder(std::mem::take(&mut self.sources), &order);
+        if !self.sources_content.is_empty() {
+            self.sources_content.resize(order.len(), None);
+            self.sources_content = reorder(std::mem::take(&mut self.sources_content), &order);
+        }
+
+        for mapping in self.mappings.0.iter_mut() {
+            if let Some(source_info) = mapping.source_info() {
+                let generated = mapping.generated();
+                let position = source_info.position;
+                let mut sorted = Mapping::new(generated.line, generated.column).with_source(
+                    new_ids
+                        .get(source_info.id as usize)
+                        .copied()
+                        .unwrap_or(source_info.id),
+                    position.line,
+                    position.column,
+                );
+                if let Some(name_id) = mapping.name_id() {
+                    sorted = sorted.with_name(name_id);
+                }
+                *mapping = sorted;
+            }
+        }
+
+        #[cfg(feature = "ignore_list")]
+        {
+            for source_id in self.ignore_list.iter_mut() {
+                if let Some(&new_id) = new_ids.get(*source_id as usize) {
+                    *source_id = new_id;
+                }
+            }
+            // keep it canonical, so that the sorted maps are identical
+            self.ignore_list.sort_unstable();
+            self.ignore_list.dedup();
+        }
+    }
+}
+
+/// Moves the items into the given order, where `order[new index] = old index`.
+fn reorder<T>(items: Vec<T>, order: &[u32]) -> Vec<T> {
+    let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .iter()
+        .map(|&idx| items[idx as usize].take().unwrap())
+        .collect()
+}
+
+fn map_cow<F>(value: &mut Cow<'_, str>, f: &mut F)
+where
+    F: FnMut(&str) -> Cow<'_, str>,
+{
+    let mapped = match f(value) {
+        Cow::Borrowed(mapped) if std::ptr::eq(mapped, value.as_ref()) => return,
+        Cow::Borrowed(mapped) => mapped.to_owned(),
+        Cow::Owned(mapped) => mapped,
+    };
+    *value = Cow::Owned(mapped);
+}
+
 impl<'a> BorrowedSourceMap<'a> {
     /// Returns a mutable reference to the names.
     ///
@@ -303,56 +848,101 @@ impl<'a> BorrowedSourceMap<'a> {
 }
 
 impl<'a> BorrowedSourceMap<'a> {
-    fn from_raw(raw: RawSourceMap<'a>) -> ParseResult<Self> {
-        if unlikely!(!matches!(raw.version, Some(3))) {
-            return Err(ParseError::UnsupportedFormat);
-        }
+    pub(crate) fn from_raw(
+        raw: RawSourceMap<'a>,
+        options: &ParseOptions,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> ParseResult<Self> {
+        Self::from_raw_filtered(raw, options, warnings, &mut |_| true)
+    }
+
+    fn from_raw_filtered<F>(
+        raw: RawSourceMap<'a>,
+        options: &ParseOptions,
+        warnings: &mut Vec<ParseWarning>,
+        keep: &mut F,
+    ) -> ParseResult<Self>
+    where
+        F: FnMut(Position) -> bool,
+    {
+        check_version(raw.version, options)?;
         #[cfg(feature = "index-map")]
         if let Some(sections) = raw.sections {
-            return Self::process_index_map(sections);
+            return Self::process_index_map(sections, raw.source_root, options, warnings, keep);
         }
 
-        Self::process_map(raw)
+        Self::process_map(raw, options, warnings, keep)
     }
 
-    fn process_map(raw: RawSourceMap<'a>) -> ParseResult<Self> {
+    #[cfg_attr(not(feature = "ignore_list"), allow(unused_variables, clippy::ptr_arg))]
+    fn process_map<F>(
+        raw: RawSourceMap<'a>,
+        options: &ParseOptions,
+        warnings: &mut Vec<ParseWarning>,
+        keep: &mut F,
+    ) -> ParseResult<Self>
+    where
+        F: FnMut(Position) -> bool,
+    {
         let file = raw.file.map(Cow::Borrowed);
 
         let source_root = raw.source_root.map(Cow::Borrowed);
 
         let sources = raw
             .sources
-            .map(|sources| Vec::from_iter(sources.into_iter().map(|s| s.map(Cow::Borrowed))))
+            .map(|sources| {
+                sources
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, source)| {
+                        Ok(raw_source(idx, source, options, warnings)?.map(Cow::Borrowed))
+                    })
+                    .collect::<ParseResult<Vec<_>>>()
+            })
+            .transpose()?
             .unwrap_or_default();
 
         let sources_len = sources.len();
+        items_count(sources_len, 0)?;
 
-        let sources_content = if let Some(sources_content) = raw.sources_content {
-            let sources_content_len = sources_content.len();
-            if sources_content_len != sources_len {
-                return Err(ParseError::MismatchSourcesContent {
-                    sources_len: sources_len as u32,
-                    sources_content_len: sources_content_len as u32,
-                });
-            }
-            Vec::from_iter(sources_content.into_iter().map(|s| s.map(Cow::Borrowed)))
-        } else {
-            Vec::from_iter(repeat_with(|| None).take(sources_len))
-        };
+        let mut sources_content = vec![];
+        raw_sources_content(
+            &mut sources_content,
+            raw.sources_content,
+            sources_len,
+            options,
+            warnings,
+        )?;
 
         let names = raw
             .names
             .map(|names| Vec::from_iter(names.into_iter().map(Cow::Borrowed)))
             .unwrap_or_default();
 
-        let names_len = names.len();
+        let (sources_len, names_len) = items_count(sources_len, names.len())?;
 
         #[cfg(feature = "ignore_list")]
-        let ignore_list = raw.ignore_list.unwrap_or_default();
+        let mut ignore_list =
+            RawSourceMap::merge_ignore_lists(raw.ignore_list, raw.x_google_ignore_list)
+                .unwrap_or_default();
+
+        // out-of-range entries are retained unless asked otherwise, since `ignore_list` is not
+        // subject to validation
+        #[cfg(feature = "ignore_list")]
+        for &source_id in &ignore_list {
+            if unlikely!(source_id >= sources_len) {
+                warnings.push(ParseWarning::IgnoreListOutOfRange(source_id));
+            }
+        }
+        #[cfg(feature = "ignore_list")]
+        if options.drop_invalid_ignore_list {
+            ignore_list.retain(|&source_id| source_id < sources_len);
+        }
 
         let mappings = MappingsDecoder::new(raw.mappings.unwrap_or_default())
-            .items_count(sources_len as u32, names_len as u32)
-            .decode()?;
+            .items_count(sources_len, names_len)
+            .options(options)
+            .decode_filtered(keep)?;
 
         Ok(Self {
             file,
@@ -368,13 +958,24 @@ impl<'a> BorrowedSourceMap<'a> {
 
     // To simplify the flattening logic of the index map, the following strategies are adopted:
     // 1. ignore the `file` attribute in all child maps,
-    // 2. concat `source_root` for each `source`,
+    // 2. concat `source_root` of the child map, and then `source_root` of the index map, for each `source`,
     // 3. merge `sources`/`names` from the child maps without performing any deduplication.
     #[cfg(feature = "index-map")]
-    fn process_index_map(
+    fn process_index_map<F>(
         sections: Vec<crate::sourcemap::raw::RawSection<'a>>,
-    ) -> ParseResult<Self> {
-        let mut mappings = Mappings::empty();
+        source_root: Option<&'a str>,
+        options: &ParseOptions,
+        warnings: &mut Vec<ParseWarning>,
+        keep: &mut F,
+    ) -> ParseResult<Self>
+    where
+        F: FnMut(Position) -> bool,
+    {
+        let top_source_root = source_root
+            .filter(|sr| !sr.is_empty())
+            .map(|sr| sr.trim_end_matches('/'));
+
+        let mut mappings = Mappings::empty()?;
         let mut names = vec![];
         let mut sources = vec![];
         let mut sources_content = vec![];
@@ -383,6 +984,8 @@ impl<'a> BorrowedSourceMap<'a> {
         let mut ignore_list = vec![];
 
         let mut last_section_end_pos: Option<Position> = None;
+        // the end sentinels of the sections, which don't count to `max_mappings`
+        let mut sentinels = 0;
         for section in sections.into_iter() {
             let current_section_start_pos = Position {
                 line: section.offset.line,
@@ -409,79 +1012,84 @@ impl<'a> BorrowedSourceMap<'a> {
                         if let Some(raw_sources) = raw.sources {
                             let raw_sources_len = raw_sources.len();
 
-                            if let Some(raw_source_root) =
-                                raw.source_root.filter(|sr| !sr.is_empty())
-                            {
-                                let source_root = raw_source_root.trim_end_matches('/');
-                                sources.extend(raw_sources.into_iter().map(|s| {
-                                    s.map(|source| {
-                                        if !source.is_empty()
-                                            && (source.starts_with('/')
-                                                || source.starts_with("http:")
-                                                || source.starts_with("https:"))
-                                        {
-                                            Cow::Borrowed(source)
-                                        } else {
-                                            Cow::Owned(format!("{}/{}", source_root, source))
+                            let source_root = raw
+                                .source_root
+                                .filter(|sr| !sr.is_empty())
+                                .map(|sr| sr.trim_end_matches('/'));
+                            for source in raw_sources {
+                                let source = raw_source(sources.len(), source, options, warnings)?;
+                                sources.push(source.map(|source| {
+                                    let source = match source_root {
+                                        Some(source_root) => {
+                                            join_source_root(source_root, Cow::Borrowed(source))
                                         }
-                                    })
+                                        None => Cow::Borrowed(source),
+                                    };
+                                    match top_source_root {
+                                        Some(top_source_root) => {
+                                            join_source_root(top_source_root, source)
+                                        }
+                                        None => source,
+                                    }
                                 }));
-                            } else {
-                                sources
-                                    .extend(raw_sources.into_iter().map(|s| s.map(Cow::Borrowed)));
                             }
 
-                            if let Some(raw_sources_content) = raw.sources_content {
-                                let raw_sources_content_len = raw_sources_content.len();
-                                if unlikely!(raw_sources_content_len != raw_sources_len) {
-                                    return Err(ParseError::MismatchSourcesContent {
-                                        sources_len: raw_sources_len as u32,
-                                        sources_content_len: raw_sources_content_len as u32,
-                                    });
-                                }
-                                sources_content.extend(
-                                    raw_sources_content
-                                        .into_iter()
-                                        .map(|s| s.map(Cow::Borrowed)),
-                                );
-                            } else {
-                                sources_content.extend(repeat_with(|| None).take(raw_sources_len));
-                            }
+                            raw_sources_content(
+                                &mut sources_content,
+                                raw.sources_content,
+                                raw_sources_len,
+                                options,
+                                warnings,
+                            )?;
                         }
                     }
 
-                    let end_sources_id = sources.len() as u32;
-                    let end_names_id = names.len() as u32;
+                    let (end_sources_id, end_names_id) = items_count(sources.len(), names.len())?;
 
                     #[cfg(feature = "ignore_list")]
-                    if let Some(raw_ignore_list) = raw.ignore_list {
+                    if let Some(raw_ignore_list) =
+                        RawSourceMap::merge_ignore_lists(raw.ignore_list, raw.x_google_ignore_list)
+                    {
                         if !raw_ignore_list.is_empty() {
                             for source_id in raw_ignore_list.into_iter() {
-                                let fixed_source_id = source_id + start_sources_id;
-                                if unlikely!(fixed_source_id >= end_sources_id) {
-                                    // skip if points to a non-existent source
+                                let fixed_source_id = source_id
+                                    .checked_add(start_sources_id)
+                                    .filter(|&id| id < end_sources_id);
+                                let Some(fixed_source_id) = fixed_source_id else {
+                                    // unlike a regular map, skip it regardless of the options,
+                                    // since the rebased id may point to a source of a later section
+                                    warnings.push(ParseWarning::IgnoreListOutOfRange(
+                                        source_id.saturating_add(start_sources_id),
+                                    ));
                                     continue;
-                                }
+                                };
                                 ignore_list.push(fixed_source_id);
                             }
                         }
                     }
 
-                    MappingsDecoder::new(raw.mappings.unwrap_or_default())
+                    let end = MappingsDecoder::new(raw.mappings.unwrap_or_default())
                         .items_count(end_sources_id, end_names_id)
+                        .options(options)
                         .state(
                             current_section_start_pos.line,
                             current_section_start_pos.column,
                             start_sources_id,
                             start_names_id,
                         )
-                        .decode_into(&mut mappings)?;
+                        .sentinels(sentinels)
+                        .decode_filtered_into(&mut mappings, &mut *keep)?;
 
-                    last_section_end_pos = mappings.last().map(|m| m.generated());
+                    sentinels += end.sentinel as usize;
+                    // the discarded mappings still occupy the section
+                    if end.position.is_some() {
+                        last_section_end_pos = end.position;
+                    }
                 }
                 None => {
                     // external maps referenced via URL are not supported,
-                    // silently ignored without error.
+                    // ignored without error.
+                    warnings.push(ParseWarning::SectionWithoutMap(current_section_start_pos));
                     last_section_end_pos = Some(current_section_start_pos)
                 }
             }
@@ -500,14 +1108,283 @@ impl<'a> BorrowedSourceMap<'a> {
     }
 }
 
+/// Checks the `version` of a map, which may be missing only if allowed.
+fn check_version(version: Option<f64>, options: &ParseOptions) -> ParseResult<()> {
+    let version = match version {
+        None if options.accept_missing_version => Some(BorrowedSourceMap::VERSION as f64),
+        version => version,
+    };
+    if unlikely!(version != Some(BorrowedSourceMap::VERSION as f64)) {
+        return Err(ParseError::UnsupportedFormat);
+    }
+    Ok(())
+}
+
+/// Appends the `sourcesContent` of a map with `sources_len` sources to `sources_content`,
+/// resizing a mismatched one if allowed.
+fn raw_sources_content<'a>(
+    sources_content: &mut Vec<Option<Cow<'a, str>>>,
+    raw: Option<Vec<Option<&'a str>>>,
+    sources_len: usize,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> ParseResult<()> {
+    let start = sources_content.len();
+    if let Some(raw) = raw {
+        let raw_len = raw.len();
+        if unlikely!(raw_len != sources_len) {
+            if !options.resize_sources_content {
+                return Err(ParseError::MismatchSourcesContent {
+                    sources_len: sources_len as u32,
+                    sources_content_len: raw_len as u32,
+                });
+            }
+            warnings.push(ParseWarning::MismatchSourcesContent {
+                sources_len: sources_len as u32,
+                sources_content_len: raw_len as u32,
+            });
+        }
+        sources_content.extend(
+            raw.into_iter()
+                .take(sources_len)
+                .map(|s| s.map(Cow::Borrowed)),
+        );
+    }
+    sources_content.resize(start + sources_len, None);
+    Ok(())
+}
+
+/// Checks an entry of `sources` at the given id, coercing an invalid one to `None` if allowed.
+fn raw_source<'a>(
+    id: usize,
+    source: RawSource<'a>,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> ParseResult<Option<&'a str>> {
+    match source {
+        RawSource::Valid(source) => Ok(source),
+        RawSource::Invalid if options.coerce_invalid_sources => {
+            warnings.push(ParseWarning::InvalidSource(id as u32));
+            Ok(None)
+        }
+        RawSource::Invalid => Err(ParseError::InvalidSource(id as u32)),
+    }
+}
+
+/// Converts the lengths of `sources` and `names` into the `u32` id range.
+fn items_count(sources_len: usize, names_len: usize) -> ParseResult<(u32, u32)> {
+    let sources_len = u32::try_from(sources_len).map_err(|_| ParseError::TooManySources)?;
+    let names_len = u32::try_from(names_len).map_err(|_| ParseError::TooManyNames)?;
+    Ok((sources_len, names_len))
+}
+
+/// Prefixes `source` with `source_root` (without trailing slashes), unless it's an absolute path or url.
+#[cfg(any(feature = "index-map", feature = "builder"))]
+pub(crate) fn join_source_root<'s>(source_root: &str, source: Cow<'s, str>) -> Cow<'s, str> {
+    if !source.is_empty()
+        && (source.starts_with('/') || source.starts_with("http:") || source.starts_with("https:"))
+    {
+        source
+    } else {
+        Cow::Owned(format!("{}/{}", source_root, source))
+    }
+}
+
 impl<'a> BorrowedSourceMap<'a> {
     /// Creates a new `BorrowedSourceMap` from a JSON buffer slice.
     ///
     /// The slice is mutable to facilitate in-place replacement of escape characters
     /// in the JSON string, allowing maximum data borrowing.
+    ///
+    /// Parsing never panics, any malformed input results in an error, so this and the other
+    /// `from_slice*` constructors can be used as fuzz targets directly:
+    ///
+    /// ```ignore
+    /// fuzz_target!(|data: &[u8]| {
+    ///     let _ = sora::BorrowedSourceMap::from_slice(&mut data.to_vec());
+    /// });
+    /// ```
     #[inline]
     pub fn from_slice(json: &'a mut [u8]) -> ParseResult<Self> {
-        Self::from_raw(RawSourceMap::from_slice(json)?)
+        #[cfg(feature = "json-comments")]
+        crate::comments::strip_comments(json);
+        Self::from_raw(
+            RawSourceMap::from_slice(json)?,
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        )
+    }
+
+    /// Creates a new `BorrowedSourceMap` from a JSON buffer slice like [from_slice](Self::from_slice),
+    /// and collects [warnings](ParseWarning) for suspicious input that is tolerated.
+    ///
+    /// Note that unrecognized properties are skipped by the JSON parser, and not reported.
+    #[inline]
+    pub fn from_slice_with_warnings(json: &'a mut [u8]) -> ParseResult<(Self, Vec<ParseWarning>)> {
+        #[cfg(feature = "json-comments")]
+        crate::comments::strip_comments(json);
+        let mut warnings = Vec::new();
+        let sm = Self::from_raw(
+            RawSourceMap::from_slice(json)?,
+            &ParseOptions::default(),
+            &mut warnings,
+        )?;
+        Ok((sm, warnings))
+    }
+
+    /// Creates a new `BorrowedSourceMap` from a JSON buffer slice like [from_slice](Self::from_slice),
+    /// decoding `mappings` with a non-standard base64 [alphabet](VlqAlphabet).
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{BorrowedSourceMap, VlqAlphabet};
+    /// let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,-_BAAA"}"#.to_vec();
+    /// let source_map = BorrowedSourceMap::from_slice_with_alphabet(&mut buf, &VlqAlphabet::URL_SAFE).unwrap();
+    /// assert_eq!(source_map.mappings()[1].generated().column, 1023);
+    /// ```
+    #[inline]
+    pub fn from_slice_with_alphabet(
+        json: &'a mut [u8],
+        alphabet: &VlqAlphabet,
+    ) -> ParseResult<Self> {
+        // the comments are stripped by `from_slice_with_options`
+        let options = ParseOptions {
+            alphabet: alphabet.clone(),
+            ..Default::default()
+        };
+        Self::from_slice_with_options(json, &options)
+    }
+
+    /// Creates a new `BorrowedSourceMap` from a JSON buffer slice like [from_slice](Self::from_slice),
+    /// with the given [options](ParseOptions).
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{BorrowedSourceMap, ParseError, ParseOptions};
+    /// let mut options = ParseOptions::default();
+    /// options.max_mappings = Some(1);
+    /// let mut buf = br#"{"version":3,"mappings":"A,C"}"#.to_vec();
+    /// assert!(matches!(
+    ///     BorrowedSourceMap::from_slice_with_options(&mut buf, &options),
+    ///     Err(ParseError::TooManyMappings { limit: 1 })
+    /// ));
+    /// ```
+    #[inline]
+    pub fn from_slice_with_options(
+        json: &'a mut [u8],
+        options: &ParseOptions,
+    ) -> ParseResult<Self> {
+        #[cfg(feature = "json-comments")]
+        crate::comments::strip_comments(json);
+        Self::from_raw(
+            RawSourceMap::from_slice_with_options(json, options)?,
+            options,
+            &mut Vec::new(),
+        )
+    }
+
+    /// Creates a new `BorrowedSourceMap` from a JSON buffer slice like [from_slice](Self::from_slice),
+    /// but only retains the mappings whose generated position passes `keep`.
+    ///
+    /// The other mappings are discarded while decoding, so looking up a single region of a huge
+    /// map doesn't need the memory for all of its mappings. Since `mappings` is relative, it's
+    /// still decoded in full. Note that the end sentinel is subject to `keep` as well, see
+    /// [insert_end_sentinel](ParseOptions::insert_end_sentinel).
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::BorrowedSourceMap;
+    /// let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;AACA;AACA"}"#.to_vec();
+    /// let sm = BorrowedSourceMap::from_slice_filtered(&mut buf, |pos| pos.line == 1).unwrap();
+    /// assert_eq!(sm.mappings().len(), 1);
+    /// assert_eq!(sm.find_mapping((1, 0)).unwrap().source_info().unwrap().position.line, 1);
+    /// ```
+    pub fn from_slice_filtered<F>(json: &'a mut [u8], mut keep: F) -> ParseResult<Self>
+    where
+        F: FnMut(Position) -> bool,
+    {
+        #[cfg(feature = "json-comments")]
+        crate::comments::strip_comments(json);
+        Self::from_raw_filtered(
+            RawSourceMap::from_slice(json)?,
+            &ParseOptions::default(),
+            &mut Vec::new(),
+            &mut keep,
+        )
+    }
+
+    /// Creates a new [BorrowedSourceMap] from a JSON buffer, with the leniency of the given
+    /// consumer, see [CompatMode].
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{BorrowedSourceMap, CompatMode};
+    /// let json = br#"{"sources":["a.js","b.js"],"sourcesContent":["a"],"mappings":"AAAA"}"#;
+    /// assert!(BorrowedSourceMap::from_slice_with_mode(&mut json.to_vec(), CompatMode::Strict).is_err());
+    /// let mut buf = json.to_vec();
+    /// let sm = BorrowedSourceMap::from_slice_with_mode(&mut buf, CompatMode::Chrome).unwrap();
+    /// assert_eq!(sm.sources_content().len(), 2);
+    /// ```
+    #[inline]
+    pub fn from_slice_with_mode(json: &'a mut [u8], mode: CompatMode) -> ParseResult<Self> {
+        Self::from_slice_with_options(json, &mode.into())
+    }
+
+    /// Creates a new `BorrowedSourceMap` from a JSON buffer slice like [from_slice](Self::from_slice),
+    /// and then runs the full [validate](Self::validate), as a single entry point to check that
+    /// the map is fully sound.
+    ///
+    /// With the `ignore_list` feature, [validate_ignore_list](Self::validate_ignore_list) is run
+    /// as well. Note that the parsing itself already checks the order and the references of
+    /// `mappings`, and the length of `sourcesContent`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{BorrowedSourceMap, LoadError, ParseError};
+    /// let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#.to_vec();
+    /// assert!(BorrowedSourceMap::from_slice_validated(&mut buf).is_ok());
+    ///
+    /// let mut buf = br#"{"version":3,"mappings":"AAAA"}"#.to_vec();
+    /// assert!(matches!(
+    ///     BorrowedSourceMap::from_slice_validated(&mut buf),
+    ///     Err(LoadError::Parse(ParseError::UnknownSourceReference(0)))
+    /// ));
+    /// ```
+    pub fn from_slice_validated(json: &'a mut [u8]) -> Result<Self, LoadError> {
+        let sm = Self::from_slice(json)?;
+        sm.validate()?;
+        #[cfg(feature = "ignore_list")]
+        sm.validate_ignore_list()?;
+        Ok(sm)
+    }
+
+    /// Creates a new `BorrowedSourceMap` from a JSON buffer slice with the given
+    /// [options](ParseOptions) like [from_slice_with_options](Self::from_slice_with_options),
+    /// and collects [warnings](ParseWarning) like [from_slice_with_warnings](Self::from_slice_with_warnings).
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{BorrowedSourceMap, ParseOptions, ParseWarning};
+    /// let mut options = ParseOptions::default();
+    /// options.coerce_invalid_sources = true;
+    /// let mut buf = br#"{"version":3,"sources":["a.js",1],"mappings":"AAAA"}"#.to_vec();
+    /// let (sm, warnings) = BorrowedSourceMap::from_slice_with_options_and_warnings(&mut buf, &options).unwrap();
+    /// assert_eq!(sm.source_at(1), None);
+    /// assert_eq!(warnings, vec![ParseWarning::InvalidSource(1)]);
+    /// ```
+    pub fn from_slice_with_options_and_warnings(
+        json: &'a mut [u8],
+        options: &ParseOptions,
+    ) -> ParseResult<(Self, Vec<ParseWarning>)> {
+        #[cfg(feature = "json-comments")]
+        crate::comments::strip_comments(json);
+        let mut warnings = Vec::new();
+        let sm = Self::from_raw(
+            RawSourceMap::from_slice_with_options(json, options)?,
+            options,
+            &mut warnings,
+        )?;
+        Ok((sm, warnings))
     }
 
     /// Creates a new `BorrowedSourceMap` from a JSON string.
@@ -517,15 +1394,68 @@ impl<'a> BorrowedSourceMap<'a> {
     #[inline]
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(json: &'a mut str) -> ParseResult<Self> {
-        Self::from_raw(RawSourceMap::from_str(json)?)
+        // SAFETY: only ASCII bytes are written, so the string remains valid UTF-8
+        #[cfg(feature = "json-comments")]
+        crate::comments::strip_comments(unsafe { json.as_bytes_mut() });
+        Self::from_raw(
+            RawSourceMap::from_str(json)?,
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        )
     }
 }
 
 impl BorrowedSourceMap<'_> {
+    /// Serializes the source map as JSON into the writer.
+    ///
+    /// The output is streamed: each field, each element of `sources`, `sourcesContent` and
+    /// `names`, and each segment of `mappings` are written as they are serialized, so the whole
+    /// map is never buffered in memory. Since this results in many small writes, wrap
+    /// unbuffered writers like files or sockets in a [BufWriter](std::io::BufWriter).
+    #[inline]
     pub fn write<W>(&self, w: &mut W) -> io::Result<()>
     where
         W: Write,
     {
+        self.write_with_options(w, &WriteOptions::default())
+    }
+
+    /// Serializes the source map as JSON into the writer like [write](Self::write),
+    /// with the given [options](WriteOptions).
+    ///
+    /// Fails with [InvalidInput](io::ErrorKind::InvalidInput) if the options conflict, or with
+    /// [InvalidData](io::ErrorKind::InvalidData) if `inline_sources` is set but the content of a
+    /// source is missing. Nothing is written on these errors.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{SourceMap, WriteOptions};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"sourcesContent":["a"],"mappings":"AAAA"}"#.to_vec()).unwrap();
+    /// let mut options = WriteOptions::default();
+    /// options.exclude_sources_content = true;
+    /// let mut buf = vec![];
+    /// sm.write_with_options(&mut buf, &options).unwrap();
+    /// assert_eq!(buf, br#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#);
+    /// ```
+    pub fn write_with_options<W>(&self, w: &mut W, options: &WriteOptions) -> io::Result<()>
+    where
+        W: Write,
+    {
+        if options.inline_sources {
+            if options.exclude_sources_content {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "`inline_sources` conflicts with `exclude_sources_content`",
+                ));
+            }
+            if let Some(source_id) = self.sources_content.iter().position(Option::is_none) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("source #{} has no content to inline", source_id),
+                ));
+            }
+        }
+
         w.write_all(br#"{"version":3"#)?;
 
         if let Some(file) = self.file.as_deref() {
@@ -533,10 +1463,17 @@ impl BorrowedSourceMap<'_> {
             file.json_write(w)?;
         }
 
+        if let Some(source_root) = self.source_root.as_deref() {
+            w.write_all(br#","sourceRoot":"#)?;
+            source_root.json_write(w)?;
+        }
+
         w.write_all(br#","sources":"#)?;
         self.sources.json_write(w)?;
-        w.write_all(br#","sourcesContent":"#)?;
-        self.sources_content.json_write(w)?;
+        if !options.exclude_sources_content {
+            w.write_all(br#","sourcesContent":"#)?;
+            self.sources_content.json_write(w)?;
+        }
         if !self.names.is_empty() {
             w.write_all(br#","names":"#)?;
             self.names.json_write(w)?;
@@ -550,6 +1487,10 @@ impl BorrowedSourceMap<'_> {
         if !self.ignore_list.is_empty() {
             w.write_all(br#","ignoreList":"#)?;
             self.ignore_list.json_write(w)?;
+            if options.legacy_ignore_list {
+                w.write_all(br#","x_google_ignoreList":"#)?;
+                self.ignore_list.json_write(w)?;
+            }
         }
 
         w.write_all(br#"}"#)
@@ -567,4 +1508,44 @@ impl BorrowedSourceMap<'_> {
         self.to_vec()
             .map(|v| unsafe { String::from_utf8_unchecked(v) })
     }
+
+    /// Serializes the map into a `sourceMappingURL` comment with a base64 `data:` URL, to be
+    /// appended to the generated code.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{CommentStyle, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":[],"mappings":""}"#.to_vec()).unwrap();
+    /// let comment = sm.to_inline_comment(CommentStyle::Legacy).unwrap();
+    /// assert!(comment.starts_with("//@ sourceMappingURL=data:application/json;"));
+    /// ```
+    pub fn to_inline_comment(&self, style: CommentStyle) -> io::Result<String> {
+        Ok(format!(
+            "{}data:application/json;charset=utf-8;base64,{}",
+            style.prefix(),
+            encode_base64(&self.to_vec()?)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::items_count;
+    use crate::ParseError;
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_items_count() {
+        let max = u32::MAX as usize;
+        assert_eq!(items_count(0, 0).unwrap(), (0, 0));
+        assert_eq!(items_count(max, max).unwrap(), (u32::MAX, u32::MAX));
+        assert!(matches!(
+            items_count(max + 1, 0),
+            Err(ParseError::TooManySources)
+        ));
+        assert!(matches!(
+            items_count(0, max + 1),
+            Err(ParseError::TooManyNames)
+        ));
+    }
 }
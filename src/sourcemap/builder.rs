@@ -1,4 +1,8 @@
-use crate::{BorrowedSourceMap, Mappings, ValidateResult};
+use crate::mappings::MappingsDecoder;
+use crate::sourcemap::borrowed::join_source_root;
+#[cfg(feature = "ignore_list")]
+use crate::ValidateError;
+use crate::{BorrowedSourceMap, Mappings, ParseResult, ValidateResult};
 use std::borrow::Cow;
 
 impl<'a> BorrowedSourceMap<'a> {
@@ -17,6 +21,7 @@ pub struct SourceMapBuilder<'a> {
     pub(crate) sources_content: Option<Vec<Option<Cow<'a, str>>>>,
     #[cfg(feature = "ignore_list")]
     pub(crate) ignore_list: Option<Vec<u32>>,
+    pub(crate) apply_source_root: bool,
 }
 
 impl<'a> SourceMapBuilder<'a> {
@@ -32,6 +37,37 @@ impl<'a> SourceMapBuilder<'a> {
         self
     }
 
+    /// Decodes the VLQ encoded `mappings` string and sets the result as the mappings.
+    ///
+    /// The ids in the string are checked against the current `sources` and `names`,
+    /// so they must be set **before** calling this method. Otherwise,
+    /// [UnknownSourceReference](crate::ParseError::UnknownSourceReference) or
+    /// [UnknownNameReference](crate::ParseError::UnknownNameReference) is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// # use std::borrow::Cow;
+    /// let sm = SourceMap::builder()
+    ///     .with_sources(vec![Some(Cow::Borrowed("a.js"))])
+    ///     .with_sources_content(vec![None])
+    ///     .with_encoded_mappings("AAAA;AACA")
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(sm.mappings().len(), 2);
+    /// ```
+    pub fn with_encoded_mappings(mut self, mappings: &str) -> ParseResult<Self> {
+        let sources_len = self.sources.as_ref().map_or(0, Vec::len);
+        let names_len = self.names.as_ref().map_or(0, Vec::len);
+        self.mappings = Some(
+            MappingsDecoder::new(mappings)
+                .items_count(sources_len as u32, names_len as u32)
+                .decode()?,
+        );
+        Ok(self)
+    }
+
     #[inline(always)]
     pub fn with_names(mut self, names: Vec<Cow<'a, str>>) -> Self {
         self.names = Some(names);
@@ -44,6 +80,32 @@ impl<'a> SourceMapBuilder<'a> {
         self
     }
 
+    /// Applies the `source_root` to the `sources` when building, the same way as the index map
+    /// flattening does, so consumers that don't honor `sourceRoot` still get the full paths.
+    ///
+    /// Relative `sources` are prefixed with the `source_root`, absolute paths and urls are kept
+    /// as is, and the `source_root` of the result is cleared. Defaults to `false`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// # use std::borrow::Cow;
+    /// let sm = SourceMap::builder()
+    ///     .with_source_root(Cow::Borrowed("src/"))
+    ///     .with_sources(vec![Some(Cow::Borrowed("a.js"))])
+    ///     .with_sources_content(vec![None])
+    ///     .with_applied_source_root(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(sm.sources()[0].as_deref(), Some("src/a.js"));
+    /// assert_eq!(sm.source_root(), &None);
+    /// ```
+    #[inline(always)]
+    pub fn with_applied_source_root(mut self, apply: bool) -> Self {
+        self.apply_source_root = apply;
+        self
+    }
+
     #[inline(always)]
     pub fn with_sources(mut self, sources: Vec<Option<Cow<'a, str>>>) -> Self {
         self.sources = Some(sources);
@@ -56,6 +118,38 @@ impl<'a> SourceMapBuilder<'a> {
         self
     }
 
+    /// Appends a source along with its content, returning the id of the source.
+    ///
+    /// `sources` and `sources_content` are extended in lockstep, so their lengths can't be
+    /// mismatched. If they are already mismatched, `sources_content` is first resized to the length
+    /// of `sources`, padding with `None`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// # use std::borrow::Cow;
+    /// let mut builder = SourceMap::builder();
+    /// let a = builder.push_source_full(Some(Cow::Borrowed("a.js")), Some(Cow::Borrowed("a")));
+    /// let b = builder.push_source_full(Some(Cow::Borrowed("b.js")), None);
+    /// let sm = builder.build().unwrap();
+    /// assert_eq!((a, b), (0, 1));
+    /// assert_eq!(sm.sources_content()[0].as_deref(), Some("a"));
+    /// ```
+    pub fn push_source_full(
+        &mut self,
+        path: Option<Cow<'a, str>>,
+        content: Option<Cow<'a, str>>,
+    ) -> u32 {
+        let sources = self.sources.get_or_insert_with(Vec::new);
+        let sources_content = self.sources_content.get_or_insert_with(Vec::new);
+        sources_content.resize(sources.len(), None);
+        sources.push(path);
+        sources_content.push(content);
+        (sources.len() - 1) as u32
+    }
+
+    /// Sets the `ignore_list`, whose entries are checked to reference existing sources
+    /// on [build](Self::build).
     #[cfg(feature = "ignore_list")]
     #[inline(always)]
     pub fn with_ignore_list(mut self, ignore_list: Vec<u32>) -> Self {
@@ -67,6 +161,14 @@ impl<'a> SourceMapBuilder<'a> {
         // SAFETY: just reuse code
         let v = unsafe { self.build_unchecked() };
         v.validate()?;
+        #[cfg(feature = "ignore_list")]
+        if let Some(&source_id) = v
+            .ignore_list
+            .iter()
+            .find(|&&id| id as usize >= v.sources.len())
+        {
+            return Err(ValidateError::UnknownSourceReference(source_id));
+        }
         Ok(v)
     }
 
@@ -77,12 +179,23 @@ impl<'a> SourceMapBuilder<'a> {
     /// This function does not validate the values. The caller must ensure that
     /// the values are valid.
     pub unsafe fn build_unchecked(self) -> BorrowedSourceMap<'a> {
+        let mut source_root = self.source_root;
+        let mut sources = self.sources.unwrap_or_default();
+        if self.apply_source_root {
+            if let Some(source_root) = source_root.take().filter(|sr| !sr.is_empty()) {
+                let source_root = source_root.trim_end_matches('/');
+                for source in sources.iter_mut().flatten() {
+                    *source = join_source_root(source_root, std::mem::take(source));
+                }
+            }
+        }
+
         BorrowedSourceMap {
             file: self.file,
             mappings: self.mappings.unwrap_or_default(),
             names: self.names.unwrap_or_default(),
-            source_root: self.source_root,
-            sources: self.sources.unwrap_or_default(),
+            source_root,
+            sources,
             sources_content: self.sources_content.unwrap_or_default(),
             #[cfg(feature = "ignore_list")]
             ignore_list: self.ignore_list.unwrap_or_default(),
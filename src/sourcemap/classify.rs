@@ -0,0 +1,56 @@
+use crate::{BorrowedSourceMap, Mapping, Position};
+
+/// `PositionClass` describes how a generated position is covered by the mappings, see
+/// [classify_position](BorrowedSourceMap::classify_position).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PositionClass {
+    /// The position is covered by the mapping, which has source information.
+    Mapped(Mapping),
+    /// The position is covered by a mapping without source information, e.g. generated code
+    /// like a bundler runtime, which has no original source.
+    UnmappedGap,
+    /// The position precedes the first mapping.
+    BeforeStart,
+    /// The position is past the end of the mappings, i.e. after the last mapped generated line,
+    /// or covered by the end sentinel: a trailing mapping without source at the start of a line
+    /// after the other mappings.
+    PastEnd,
+}
+
+impl BorrowedSourceMap<'_> {
+    /// Classifies a generated position, which tells apart the reasons for
+    /// [find_mapping](Self::find_mapping) not returning source information.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{PositionClass, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"CAAA,C;"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.classify_position((0, 0)), PositionClass::BeforeStart);
+    /// assert!(matches!(sm.classify_position((0, 1)), PositionClass::Mapped(_)));
+    /// assert_eq!(sm.classify_position((0, 2)), PositionClass::UnmappedGap);
+    /// assert_eq!(sm.classify_position((1, 0)), PositionClass::PastEnd);
+    /// ```
+    pub fn classify_position<P>(&self, pos: P) -> PositionClass
+    where
+        P: Into<Position>,
+    {
+        let pos = pos.into();
+        let (Some(mapping), Some(last)) = (self.mappings.find_mapping(pos), self.mappings.last())
+        else {
+            return PositionClass::BeforeStart;
+        };
+        let sentinel = matches!(
+            &self.mappings[..],
+            [.., prev, last] if last.is_source_less()
+                && last.generated().column == 0
+                && prev.generated().line < last.generated().line
+        );
+        if pos.line > last.generated().line || (sentinel && mapping == *last) {
+            PositionClass::PastEnd
+        } else if mapping.is_source_less() {
+            PositionClass::UnmappedGap
+        } else {
+            PositionClass::Mapped(mapping)
+        }
+    }
+}
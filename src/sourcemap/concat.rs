@@ -0,0 +1,97 @@
+use crate::{BorrowedSourceMap, ConcatError, Mappings, Position, ValidateError, ValidateResult};
+
+impl<'a> BorrowedSourceMap<'a> {
+    /// Appends another source map, whose generated file is placed at `line_offset` of the
+    /// generated file of this one.
+    ///
+    /// The sources, contents and names of `other` are appended without deduplication, and its
+    /// mappings are shifted and rebased accordingly.
+    ///
+    /// The shifted first mapping of `other` must be strictly after the last mapping of this map,
+    /// otherwise [ConcatError::Overlap] is returned, since lookups would become wrong.
+    /// A trailing mapping without source at exactly that position, which only marks the end
+    /// of this map, is dropped instead. [ConcatError::TooManySources] or
+    /// [ConcatError::TooManyNames] is returned if the combined ids exceed the `u32` range.
+    /// Nothing is changed on error.
+    pub fn concat(
+        &mut self,
+        other: &BorrowedSourceMap<'a>,
+        line_offset: u32,
+    ) -> Result<(), ConcatError> {
+        let source_id_base = self.sources.len() as u32;
+        let name_id_base = self.names.len() as u32;
+        // `u32::MAX` is reserved for a missing id
+        if self.sources.len() + other.sources.len() >= u32::MAX as usize {
+            return Err(ConcatError::TooManySources);
+        }
+        if self.names.len() + other.names.len() >= u32::MAX as usize {
+            return Err(ConcatError::TooManyNames);
+        }
+
+        // index of the end marker to drop
+        let mut end_marker = None;
+        if let (Some(last), Some(first)) = (self.mappings.last(), other.mappings.first()) {
+            let first = Position::new(
+                first
+                    .generated()
+                    .line
+                    .checked_add(line_offset)
+                    .ok_or(ConcatError::PositionOverflow)?,
+                first.generated().column,
+            );
+            let last_pos = last.generated();
+            if first == last_pos && !last.has_source() {
+                end_marker = Some(self.mappings.len() - 1);
+            } else if first <= last_pos {
+                return Err(ConcatError::Overlap {
+                    last: last_pos,
+                    first,
+                });
+            }
+        }
+
+        self.mappings
+            .extend_offset(&other.mappings, line_offset, source_id_base, name_id_base)
+            .map_err(|err| match err {
+                ValidateError::PositionOverflow => ConcatError::PositionOverflow,
+                ValidateError::UnknownSourceReference(_) => ConcatError::TooManySources,
+                ValidateError::UnknownNameReference(_) => ConcatError::TooManyNames,
+                // the order has been checked above
+                _ => unreachable!(),
+            })?;
+        if let Some(idx) = end_marker {
+            self.mappings.0.remove(idx);
+        }
+
+        self.sources.extend(other.sources.iter().cloned());
+        self.sources_content.resize(source_id_base as usize, None);
+        self.sources_content
+            .extend(other.sources_content.iter().cloned());
+        self.sources_content.resize(self.sources.len(), None);
+        self.names.extend(other.names.iter().cloned());
+
+        #[cfg(feature = "ignore_list")]
+        self.ignore_list.extend(
+            other
+                .ignore_list
+                .iter()
+                // an entry which overflows references no source, so it's dropped
+                .filter_map(|id| id.checked_add(source_id_base)),
+        );
+
+        Ok(())
+    }
+
+    /// Appends the names of `other` to the names of this map, and returns the mappings of
+    /// `other` with their name ids rebased onto the combined names, see [Mappings::rebase_names].
+    ///
+    /// Unlike reusing the mappings of `other` as is, each name id still refers to the same
+    /// string, even if the maps have names at overlapping ids. The names are appended without
+    /// deduplication, like [concat](Self::concat) does. Nothing is changed on error.
+    pub fn merge_names(&mut self, other: &BorrowedSourceMap<'a>) -> ValidateResult<Mappings> {
+        let mut mappings = other.mappings.clone();
+        mappings.rebase_names(self.names.len() as u32)?;
+        self.names.extend(other.names.iter().cloned());
+        Ok(mappings)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{BorrowedSourceMap, Mapping};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+impl BorrowedSourceMap<'_> {
+    /// Deduplicates repeated entries in `names` and `sources`, and updates the ids referenced
+    /// in the `mappings` accordingly.
+    ///
+    /// Sources are only merged when their paths, contents and presence in `ignore_list` are all
+    /// identical. Sources without a path are never merged, neither are any sources if
+    /// `sources_content` doesn't match the `sources` in length.
+    ///
+    /// Minified files usually reference the same names many times, so deduplicating can reduce
+    /// memory usage considerably, especially for [SourceMap](crate::SourceMap) whose strings
+    /// are all owned. The remaining strings are not shared with other maps.
+    pub fn dedup_names_and_sources(&mut self) {
+        let name_ids =
+            dedup_ids(self.names.iter().map(|name| Some(name.as_ref()))).map(|(keep, ids)| {
+                retain_by(&mut self.names, &keep);
+                ids
+            });
+        let source_ids = if self.sources_content.is_empty()
+            || self.sources_content.len() == self.sources.len()
+        {
+            #[cfg(feature = "ignore_list")]
+            let ignore_list = &self.ignore_list;
+            let is_ignored = |_idx: usize| {
+                #[cfg(feature = "ignore_list")]
+                return ignore_list.contains(&(_idx as u32));
+                #[cfg(not(feature = "ignore_list"))]
+                return false;
+            };
+            let sources_content = &self.sources_content;
+            let keep = dedup_ids(self.sources.iter().enumerate().map(|(idx, source)| {
+                source.as_deref().map(|source| {
+                    (
+                        source,
+                        sources_content.get(idx).and_then(|c| c.as_deref()),
+                        is_ignored(idx),
+                    )
+                })
+            }));
+            keep.map(|(keep, ids)| {
+                retain_by(&mut self.sources, &keep);
+                if !self.sources_content.is_empty() {
+                    retain_by(&mut self.sources_content, &keep);
+                }
+                ids
+            })
+        } else {
+            None
+        };
+
+        if name_ids.is_none() && source_ids.is_none() {
+            return;
+        }
+
+        let remap = |ids: &Option<Vec<u32>>, id: u32| {
+            ids.as_ref()
+                .and_then(|ids| ids.get(id as usize).copied())
+                .unwrap_or(id)
+        };
+
+        for mapping in self.mappings.0.iter_mut() {
+            if let Some(source_info) = mapping.source_info() {
+                let generated = mapping.generated();
+                let position = source_info.position;
+                let mut deduped = Mapping::new(generated.line, generated.column).with_source(
+                    remap(&source_ids, source_info.id),
+                    position.line,
+                    position.column,
+                );
+                if let Some(name_id) = mapping.name_id() {
+                    deduped = deduped.with_name(remap(&name_ids, name_id));
+                }
+                *mapping = deduped;
+            }
+        }
+
+        #[cfg(feature = "ignore_list")]
+        if source_ids.is_some() {
+            let mut ignore_list = Vec::with_capacity(self.ignore_list.len());
+            for &source_id in &self.ignore_list {
+                let source_id = remap(&source_ids, source_id);
+                if !ignore_list.contains(&source_id) {
+                    ignore_list.push(source_id);
+                }
+            }
+            self.ignore_list = ignore_list;
+        }
+    }
+}
+
+/// Returns whether each item should be kept, and the new id of each item.
+/// Items with a `None` key are always kept.
+fn dedup_ids<K, I>(keys: I) -> Option<(Vec<bool>, Vec<u32>)>
+where
+    K: Hash + Eq,
+    I: ExactSizeIterator<Item = Option<K>>,
+{
+    let len = keys.len();
+    let mut seen = HashMap::with_capacity(len);
+    let mut keep = Vec::with_capacity(len);
+    let mut ids = Vec::with_capacity(len);
+    let mut next_id = 0;
+
+    for key in keys {
+        let existing = key.and_then(|key| match seen.get(&key) {
+            Some(&id) => Some(id),
+            None => {
+                seen.insert(key, next_id);
+                None
+            }
+        });
+        match existing {
+            Some(id) => {
+                keep.push(false);
+                ids.push(id);
+            }
+            None => {
+                keep.push(true);
+                ids.push(next_id);
+                next_id += 1;
+            }
+        }
+    }
+
+    if next_id as usize == len {
+        None
+    } else {
+        Some((keep, ids))
+    }
+}
+
+fn retain_by<T>(items: &mut Vec<T>, keep: &[bool]) {
+    let mut idx = 0;
+    items.retain(|_| {
+        idx += 1;
+        keep[idx - 1]
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BorrowedSourceMap;
+
+    #[test]
+    fn test_mismatched_sources_content() {
+        let mut buf = br#"{"version":3,"sources":["a.js","b.js","a.js"],"sourcesContent":["a",null,"a"],"names":["x","x"],"mappings":"AAAAA,CCAAC,CCAA"}"#.to_vec();
+        let mut sm = BorrowedSourceMap::from_slice(&mut buf).unwrap();
+        // only reachable without validation, e.g. `build_unchecked`
+        sm.sources_content.pop();
+        let sources = sm.sources.clone();
+
+        sm.dedup_names_and_sources();
+        assert_eq!(sm.names.len(), 1);
+        // the contents can't be matched to the sources
+        assert_eq!(sm.sources, sources);
+        assert_eq!(sm.sources_content.len(), 2);
+    }
+}
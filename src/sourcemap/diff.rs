@@ -0,0 +1,126 @@
+use crate::{BorrowedSourceMap, Mapping, Position, ResolvedMapping};
+use std::collections::HashSet;
+
+/// `SourceMapDiff` summarizes the differences between two source maps, see
+/// [BorrowedSourceMap::diff].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SourceMapDiff<'s> {
+    /// The sources only in the other map, in its order.
+    pub added_sources: Vec<&'s str>,
+    /// The sources only in this map, in its order.
+    pub removed_sources: Vec<&'s str>,
+    /// The number of mappings in either map without an equal mapping in the other.
+    ///
+    /// Mappings are compared by their generated position, source path, original position and
+    /// name, so renumbering the sources or names is not a change.
+    pub changed_mappings: usize,
+    /// The first generated position where finding a mapping gives different results.
+    pub first_divergence: Option<Position>,
+}
+
+impl SourceMapDiff<'_> {
+    /// Checks if the maps are equivalent.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.added_sources.is_empty()
+            && self.removed_sources.is_empty()
+            && self.changed_mappings == 0
+            && self.first_divergence.is_none()
+    }
+}
+
+impl BorrowedSourceMap<'_> {
+    /// Summarizes the differences from this map to `other`, e.g. for CI to flag unexpected
+    /// changes of generated source maps, which is more actionable than a byte diff.
+    ///
+    /// This sorts the mappings of both maps, which takes O(n log n) time.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Position, SourceMap};
+    /// let old = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;AACA"}"#.to_vec()).unwrap();
+    /// let new = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;AAEA"}"#.to_vec()).unwrap();
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.changed_mappings, 2);
+    /// assert_eq!(diff.first_divergence, Some(Position::new(1, 0)));
+    /// ```
+    pub fn diff<'s>(&'s self, other: &'s BorrowedSourceMap) -> SourceMapDiff<'s> {
+        let sources = |map: &'s BorrowedSourceMap| {
+            map.sources
+                .iter()
+                .filter_map(|source| source.as_deref())
+                .collect::<Vec<_>>()
+        };
+        let (old_sources, new_sources) = (sources(self), sources(other));
+        let (old_set, new_set) = (
+            old_sources.iter().collect::<HashSet<_>>(),
+            new_sources.iter().collect::<HashSet<_>>(),
+        );
+        let added_sources = new_sources
+            .iter()
+            .filter(|source| !old_set.contains(source))
+            .copied()
+            .collect();
+        let removed_sources = old_sources
+            .iter()
+            .filter(|source| !new_set.contains(source))
+            .copied()
+            .collect();
+
+        SourceMapDiff {
+            added_sources,
+            removed_sources,
+            changed_mappings: self.changed_mappings(other),
+            first_divergence: self.first_divergence(other),
+        }
+    }
+
+    fn changed_mappings(&self, other: &BorrowedSourceMap) -> usize {
+        let (old, new) = (resolve_all(self), resolve_all(other));
+
+        // the size of the symmetric difference of the sorted multisets
+        let (mut i, mut j, mut changed) = (0, 0, 0);
+        while i < old.len() && j < new.len() {
+            match old[i].cmp(&new[j]) {
+                std::cmp::Ordering::Less => {
+                    changed += 1;
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    changed += 1;
+                    j += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        changed + (old.len() - i) + (new.len() - j)
+    }
+
+    fn first_divergence(&self, other: &BorrowedSourceMap) -> Option<Position> {
+        let mut positions = self
+            .mappings
+            .iter()
+            .chain(other.mappings.iter())
+            .map(Mapping::generated)
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions.dedup();
+
+        let (old_finder, new_finder) = (self.finder(), other.finder());
+        positions.into_iter().find(|&pos| {
+            let old = old_finder.find_mapping(pos).map(|m| self.resolve(&m));
+            let new = new_finder.find_mapping(pos).map(|m| other.resolve(&m));
+            old != new
+        })
+    }
+}
+
+/// Resolves the mappings of the map, in sorted order.
+fn resolve_all<'s>(map: &'s BorrowedSourceMap) -> Vec<ResolvedMapping<'s>> {
+    let mut resolved = map.resolved_mappings().collect::<Vec<_>>();
+    resolved.sort_unstable();
+    resolved
+}
@@ -0,0 +1,75 @@
+use crate::sourcemap::raw::{RawSource, RawSourceMap};
+use crate::{BorrowedSourceMap, ParseOptions, ParseResult, SourceMap};
+
+/// The fields of a source map, for callers who already have them at hand, e.g. from another
+/// serialization format, and want sora to decode the mappings and validate the map.
+///
+/// # Example
+/// ```
+/// # use sora::{SourceMap, SourceMapInput};
+/// let sm = SourceMap::from_input(SourceMapInput {
+///     sources: vec![Some("a.js")],
+///     mappings: "AAAA,CAAC",
+///     ..Default::default()
+/// }).unwrap();
+/// assert_eq!(sm.mappings().len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceMapInput<'a> {
+    /// Defaults to `3`, the only supported version.
+    pub version: u32,
+    pub file: Option<&'a str>,
+    pub source_root: Option<&'a str>,
+    pub sources: Vec<Option<&'a str>>,
+    /// If present, its length must match the length of `sources`.
+    pub sources_content: Option<Vec<Option<&'a str>>>,
+    pub names: Vec<&'a str>,
+    /// The VLQ-encoded mappings string.
+    pub mappings: &'a str,
+    #[cfg(feature = "ignore_list")]
+    pub ignore_list: Vec<u32>,
+}
+
+impl Default for SourceMapInput<'_> {
+    fn default() -> Self {
+        Self {
+            version: BorrowedSourceMap::VERSION,
+            file: None,
+            source_root: None,
+            sources: vec![],
+            sources_content: None,
+            names: vec![],
+            mappings: "",
+            #[cfg(feature = "ignore_list")]
+            ignore_list: vec![],
+        }
+    }
+}
+
+impl SourceMap {
+    /// Creates a new owned [SourceMap] from its fields, decoding the mappings string.
+    ///
+    /// The same checks as parsing from JSON apply, e.g. the mappings must only reference
+    /// existing sources and names.
+    pub fn from_input(input: SourceMapInput<'_>) -> ParseResult<Self> {
+        let raw = RawSourceMap {
+            version: Some(input.version as f64),
+            file: input.file,
+            sources: Some(input.sources.into_iter().map(RawSource::Valid).collect()),
+            source_root: input.source_root,
+            sources_content: input.sources_content,
+            names: Some(input.names),
+            mappings: Some(input.mappings),
+            #[cfg(feature = "ignore_list")]
+            ignore_list: Some(input.ignore_list),
+            #[cfg(feature = "ignore_list")]
+            x_google_ignore_list: None,
+            #[cfg(feature = "index-map")]
+            sections: None,
+        };
+        Ok(
+            BorrowedSourceMap::from_raw(raw, &ParseOptions::default(), &mut Vec::new())?
+                .into_owned(),
+        )
+    }
+}
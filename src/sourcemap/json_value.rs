@@ -0,0 +1,139 @@
+use crate::sourcemap::raw::{RawSource, RawSourceMap};
+use crate::{BorrowedSourceMap, ParseError, ParseOptions, ParseResult, SourceMap};
+use serde_json::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum ValueError {
+    #[error("expected a JSON object")]
+    NotObject,
+    #[error("field `{0}` has an invalid type")]
+    InvalidField(&'static str),
+}
+
+impl From<ValueError> for ParseError {
+    fn from(value: ValueError) -> Self {
+        Self::Syntax(Box::new(value))
+    }
+}
+
+impl SourceMap {
+    /// Creates a new owned [SourceMap] from a [serde_json::Value], such as a source map nested
+    /// inside a larger JSON document, without serializing the value back to JSON.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let document: serde_json::Value = serde_json::from_str(
+    ///     r#"{"sourceMap":{"version":3,"sources":["a.js"],"mappings":"AAAA"}}"#,
+    /// ).unwrap();
+    /// let sm = SourceMap::from_json_value(&document["sourceMap"]).unwrap();
+    /// assert_eq!(sm.sources()[0].as_deref(), Some("a.js"));
+    /// ```
+    pub fn from_json_value(value: &Value) -> ParseResult<Self> {
+        let raw = raw_from_value(value)?;
+        Ok(
+            BorrowedSourceMap::from_raw(raw, &ParseOptions::default(), &mut Vec::new())?
+                .into_owned(),
+        )
+    }
+}
+
+fn raw_from_value(value: &Value) -> ParseResult<RawSourceMap<'_>> {
+    if !value.is_object() {
+        return Err(ValueError::NotObject.into());
+    }
+    Ok(RawSourceMap {
+        version: field(value, "version")
+            .map(|v| v.as_f64().ok_or(ValueError::InvalidField("version")))
+            .transpose()?,
+        file: str_field(value, "file")?,
+        sources: array_field(value, "sources", |v| {
+            Ok(nullable_str(v).map_or(RawSource::Invalid, RawSource::Valid))
+        })?,
+        source_root: str_field(value, "sourceRoot")?,
+        sources_content: array_field(value, "sourcesContent", nullable_str)?,
+        names: array_field(value, "names", |v| v.as_str().ok_or(()))?,
+        mappings: str_field(value, "mappings")?,
+        #[cfg(feature = "ignore_list")]
+        ignore_list: array_field(value, "ignoreList", |v| {
+            v.as_u64().and_then(|v| u32::try_from(v).ok()).ok_or(())
+        })?,
+        #[cfg(feature = "ignore_list")]
+        x_google_ignore_list: array_field(value, "x_google_ignoreList", |v| {
+            v.as_u64().and_then(|v| u32::try_from(v).ok()).ok_or(())
+        })?,
+        #[cfg(feature = "index-map")]
+        sections: sections_from_value(value)?,
+    })
+}
+
+#[cfg(feature = "index-map")]
+fn sections_from_value(
+    value: &Value,
+) -> ParseResult<Option<Vec<crate::sourcemap::raw::RawSection<'_>>>> {
+    use crate::sourcemap::raw::{RawSection, RawSectionOffset};
+
+    let Some(sections) = field(value, "sections") else {
+        return Ok(None);
+    };
+    let sections = sections
+        .as_array()
+        .ok_or(ValueError::InvalidField("sections"))?;
+    sections
+        .iter()
+        .map(|section| {
+            let offset = section
+                .get("offset")
+                .ok_or(ValueError::InvalidField("offset"))?;
+            let offset_field = |key| {
+                offset
+                    .get(key)
+                    .and_then(Value::as_u64)
+                    .and_then(|v| u32::try_from(v).ok())
+                    .ok_or(ValueError::InvalidField("offset"))
+            };
+            Ok(RawSection {
+                offset: RawSectionOffset {
+                    line: offset_field("line")?,
+                    column: offset_field("column")?,
+                },
+                map: field(section, "map").map(raw_from_value).transpose()?,
+            })
+        })
+        .collect::<ParseResult<_>>()
+        .map(Some)
+}
+
+/// Returns the field, treating `null` as missing.
+fn field<'v>(value: &'v Value, key: &str) -> Option<&'v Value> {
+    value.get(key).filter(|v| !v.is_null())
+}
+
+fn str_field<'v>(value: &'v Value, key: &'static str) -> Result<Option<&'v str>, ValueError> {
+    field(value, key)
+        .map(|v| v.as_str().ok_or(ValueError::InvalidField(key)))
+        .transpose()
+}
+
+fn array_field<'v, T>(
+    value: &'v Value,
+    key: &'static str,
+    item: impl Fn(&'v Value) -> Result<T, ()>,
+) -> Result<Option<Vec<T>>, ValueError> {
+    field(value, key)
+        .map(|v| {
+            v.as_array()
+                .ok_or(())
+                .and_then(|items| items.iter().map(&item).collect())
+                .map_err(|_| ValueError::InvalidField(key))
+        })
+        .transpose()
+}
+
+fn nullable_str(value: &Value) -> Result<Option<&str>, ()> {
+    if value.is_null() {
+        Ok(None)
+    } else {
+        value.as_str().map(Some).ok_or(())
+    }
+}
@@ -0,0 +1,446 @@
+use crate::sourcemap::raw::RawSourceMap;
+use crate::sourcemap::BorrowedSourceMap;
+use crate::{Mapping, MappingFinder, Mappings, ParseError, ParseOptions, ParseResult, Position};
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::ops::Range;
+
+/// `LazySourceMap` is a [BorrowedSourceMap] whose `sourcesContent` entries are kept as raw
+/// JSON string literals, and only unescaped on first access via
+/// [source_content](Self::source_content).
+///
+/// This saves the unescaping of large contents that are never read, e.g. when only the
+/// mappings of a map are needed. The parts of the map other than the contents are available
+/// via the same accessors as [BorrowedSourceMap], use [into_source_map](Self::into_source_map)
+/// to get a map with all the contents, e.g. for serialization.
+///
+/// Only the top-level `sourcesContent` is lazy, the contents of the sections of an index map
+/// are parsed upfront.
+///
+/// # Example
+/// ```
+/// # use sora::LazySourceMap;
+/// let mut buf = br#"{"version":3,"sources":["a.js"],"sourcesContent":["let a = \"\u00e9\";"],"mappings":"AAAA"}"#.to_vec();
+/// let sm = LazySourceMap::from_slice(&mut buf).unwrap();
+/// assert_eq!(sm.mappings().len(), 1);
+/// assert_eq!(sm.source_content(0).unwrap(), Some("let a = \"é\";"));
+/// ```
+#[derive(Debug)]
+pub struct LazySourceMap<'a> {
+    map: BorrowedSourceMap<'a>,
+    raw_content: Vec<Option<&'a [u8]>>,
+    content: Vec<OnceCell<Cow<'a, str>>>,
+}
+
+impl<'a> LazySourceMap<'a> {
+    /// Parses the JSON like [BorrowedSourceMap::from_slice], except for the `sourcesContent`.
+    ///
+    /// The `sourcesContent` member is moved to the end of the buffer before parsing the rest,
+    /// so the buffer is left reordered.
+    pub fn from_slice(json: &'a mut [u8]) -> ParseResult<Self> {
+        #[cfg(feature = "json-comments")]
+        crate::comments::strip_comments(json);
+
+        let (json, raw_content) = match move_sources_content(json) {
+            Some((moved_len, entries)) => {
+                let (json, moved) = json.split_at_mut(json.len() - moved_len);
+                let moved = &*moved;
+                let entries = entries
+                    .into_iter()
+                    .map(|entry| entry.map(|range| &moved[range]))
+                    .collect::<Vec<_>>();
+                (json, Some(entries))
+            }
+            None => (json, None),
+        };
+
+        let options = ParseOptions::default();
+        let raw = RawSourceMap::from_slice_with_options(json, &options)?;
+        let map = BorrowedSourceMap::from_raw(raw, &options, &mut Vec::new())?;
+
+        let raw_content = raw_content.unwrap_or_else(|| vec![None; map.sources.len()]);
+        if raw_content.len() != map.sources.len() {
+            return Err(ParseError::MismatchSourcesContent {
+                sources_len: map.sources.len() as u32,
+                sources_content_len: raw_content.len() as u32,
+            });
+        }
+
+        let content = raw_content.iter().map(|_| OnceCell::new()).collect();
+        Ok(Self {
+            map,
+            raw_content,
+            content,
+        })
+    }
+
+    /// Returns the content of the source, unescaping it on first access.
+    ///
+    /// Fails with [InvalidSourceContent](ParseError::InvalidSourceContent) if the raw content
+    /// is not a valid JSON string.
+    pub fn source_content(&self, id: u32) -> ParseResult<Option<&str>> {
+        let Some(&Some(raw)) = self.raw_content.get(id as usize) else {
+            return Ok(self.map.source_content_at(id));
+        };
+        let cell = &self.content[id as usize];
+        if let Some(content) = cell.get() {
+            return Ok(Some(content));
+        }
+        let content = unescape(raw).ok_or(ParseError::InvalidSourceContent(id))?;
+        Ok(Some(cell.get_or_init(|| content)))
+    }
+
+    /// Converts into a [BorrowedSourceMap], unescaping the contents not accessed yet.
+    pub fn into_source_map(self) -> ParseResult<BorrowedSourceMap<'a>> {
+        let mut map = self.map;
+        let entries = self.raw_content.into_iter().zip(self.content);
+        for (id, (raw, cell)) in entries.enumerate() {
+            let Some(raw) = raw else {
+                continue;
+            };
+            let content = match cell.into_inner() {
+                Some(content) => content,
+                None => unescape(raw).ok_or(ParseError::InvalidSourceContent(id as u32))?,
+            };
+            map.sources_content[id] = Some(content);
+        }
+        Ok(map)
+    }
+}
+
+// The inner map is not exposed as a whole, e.g. via `Deref`, since its contents are `None`,
+// which would make the content-reading methods and the serialization silently wrong.
+impl<'a> LazySourceMap<'a> {
+    /// see [BorrowedSourceMap::file].
+    #[inline]
+    pub fn file(&self) -> &Option<Cow<'a, str>> {
+        self.map.file()
+    }
+
+    /// see [BorrowedSourceMap::source_root].
+    #[inline]
+    pub fn source_root(&self) -> &Option<Cow<'a, str>> {
+        self.map.source_root()
+    }
+
+    /// see [BorrowedSourceMap::sources].
+    #[inline]
+    pub fn sources(&self) -> &[Option<Cow<'a, str>>] {
+        self.map.sources()
+    }
+
+    /// see [BorrowedSourceMap::source_at].
+    #[inline]
+    pub fn source_at(&self, id: u32) -> Option<&str> {
+        self.map.source_at(id)
+    }
+
+    /// see [BorrowedSourceMap::names].
+    #[inline]
+    pub fn names(&self) -> &[Cow<'a, str>] {
+        self.map.names()
+    }
+
+    /// see [BorrowedSourceMap::name_at].
+    #[inline]
+    pub fn name_at(&self, id: u32) -> Option<&str> {
+        self.map.name_at(id)
+    }
+
+    /// see [BorrowedSourceMap::mappings].
+    #[inline]
+    pub fn mappings(&self) -> &Mappings {
+        self.map.mappings()
+    }
+
+    /// see [BorrowedSourceMap::find_mapping].
+    #[inline]
+    pub fn find_mapping<P>(&self, pos: P) -> Option<Mapping>
+    where
+        P: Into<Position>,
+    {
+        self.map.find_mapping(pos)
+    }
+
+    /// see [BorrowedSourceMap::finder].
+    #[inline]
+    pub fn finder(&self) -> MappingFinder {
+        self.map.finder()
+    }
+
+    /// see [BorrowedSourceMap::ignore_list].
+    #[cfg(feature = "ignore_list")]
+    #[inline]
+    pub fn ignore_list(&self) -> &[u32] {
+        self.map.ignore_list()
+    }
+}
+
+/// Finds the top-level `sourcesContent` member whose value is an array of strings and nulls,
+/// and moves it along with an adjacent comma to the end of the buffer, so that the rest is
+/// still a valid JSON object.
+///
+/// Returns the length of the moved bytes, and the ranges of the string entries without quotes
+/// within them. Returns `None` if there is no such member, leaving the buffer untouched for
+/// the parser, which also reports the errors.
+fn move_sources_content(json: &mut [u8]) -> Option<(usize, Vec<Option<Range<usize>>>)> {
+    let mut scanner = Scanner { json, idx: 0 };
+    scanner.eat(b'{')?;
+
+    let mut prev_comma = None;
+    loop {
+        scanner.skip_whitespace();
+        let member_start = scanner.idx;
+        let key = scanner.string()?;
+        scanner.eat(b':')?;
+        let entries = if &scanner.json[key] == b"sourcesContent" {
+            Some(scanner.content_array()?)
+        } else {
+            scanner.value()?;
+            None
+        };
+        let value_end = scanner.idx;
+        scanner.skip_whitespace();
+        let next = scanner.peek()?;
+
+        if let Some(entries) = entries {
+            let (start, end) = match (next, prev_comma) {
+                (b',', _) => (member_start, scanner.idx + 1),
+                (_, Some(comma)) => (comma, value_end),
+                _ => (member_start, value_end),
+            };
+            let entries = entries
+                .into_iter()
+                .map(|entry| entry.map(|range| range.start - start..range.end - start))
+                .collect();
+            json[start..].rotate_left(end - start);
+            return Some((end - start, entries));
+        }
+
+        if next != b',' {
+            return None;
+        }
+        prev_comma = Some(scanner.idx);
+        scanner.idx += 1;
+    }
+}
+
+struct Scanner<'s> {
+    json: &'s [u8],
+    idx: usize,
+}
+
+impl Scanner<'_> {
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.json.get(self.idx).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.idx += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.peek()? == byte).then(|| self.idx += 1)
+    }
+
+    /// Skips a string literal, and returns the range of its content.
+    fn string(&mut self) -> Option<Range<usize>> {
+        self.eat(b'"')?;
+        let start = self.idx;
+        loop {
+            match self.peek()? {
+                b'\\' => self.idx += 2,
+                b'"' => break,
+                _ => self.idx += 1,
+            }
+        }
+        let end = self.idx;
+        self.idx += 1;
+        Some(start..end)
+    }
+
+    /// Skips a value of any type, without checking its syntax.
+    fn value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'"' => self.string().map(drop),
+            b'{' | b'[' => {
+                let mut depth = 0;
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.idx += 1;
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.idx += 1;
+                }
+            }
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r')
+                ) {
+                    self.idx += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    /// Scans an array of strings and nulls, and returns the ranges of the string contents.
+    fn content_array(&mut self) -> Option<Vec<Option<Range<usize>>>> {
+        self.eat(b'[')?;
+        let mut entries = vec![];
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.idx += 1;
+            return Some(entries);
+        }
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                b'"' => entries.push(Some(self.string()?)),
+                b'n' if self.json[self.idx..].starts_with(b"null") => {
+                    self.idx += 4;
+                    entries.push(None);
+                }
+                _ => return None,
+            }
+            self.skip_whitespace();
+            match self.peek()? {
+                b',' => self.idx += 1,
+                b']' => {
+                    self.idx += 1;
+                    return Some(entries);
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// Unescapes the content of a JSON string literal, borrowing it if there is no escape.
+fn unescape(raw: &[u8]) -> Option<Cow<'_, str>> {
+    let raw = std::str::from_utf8(raw).ok()?;
+    if memchr::memchr(b'\\', raw.as_bytes()).is_none() {
+        return (!raw.bytes().any(|b| b < 0x20)).then_some(Cow::Borrowed(raw));
+    }
+
+    let mut unescaped = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next()? {
+                '"' => '"',
+                '\\' => '\\',
+                '/' => '/',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let high = hex4(&mut chars)?;
+                    if (0xD800..0xDC00).contains(&high) {
+                        if chars.next()? != '\\' || chars.next()? != 'u' {
+                            return None;
+                        }
+                        let low = hex4(&mut chars)?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return None;
+                        }
+                        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))?
+                    } else {
+                        char::from_u32(high)?
+                    }
+                }
+                _ => return None,
+            },
+            c if (c as u32) < 0x20 => return None,
+            c => c,
+        };
+        unescaped.push(c);
+    }
+    Some(Cow::Owned(unescaped))
+}
+
+fn hex4(chars: &mut std::str::Chars) -> Option<u32> {
+    let mut value = 0;
+    for _ in 0..4 {
+        value = value * 16 + chars.next()?.to_digit(16)?;
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{move_sources_content, unescape};
+    use std::borrow::Cow;
+
+    fn moved(json: &str) -> Option<(String, String)> {
+        let mut json = json.as_bytes().to_vec();
+        let (len, _) = move_sources_content(&mut json)?;
+        let (rest, moved) = json.split_at(json.len() - len);
+        Some((
+            String::from_utf8(rest.to_vec()).unwrap(),
+            String::from_utf8(moved.to_vec()).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn test_move_sources_content() {
+        assert_eq!(
+            moved(r#"{"sourcesContent":["a"],"mappings":""}"#).unwrap(),
+            (
+                r#"{"mappings":""}"#.into(),
+                r#""sourcesContent":["a"],"#.into()
+            )
+        );
+        assert_eq!(
+            moved(r#"{"mappings":"", "sourcesContent" : [null, "a\"]"] }"#).unwrap(),
+            (
+                r#"{"mappings":"" }"#.into(),
+                r#", "sourcesContent" : [null, "a\"]"]"#.into()
+            )
+        );
+        assert_eq!(
+            moved(r#"{"sourcesContent":[]}"#).unwrap(),
+            ("{}".into(), r#""sourcesContent":[]"#.into())
+        );
+        // left to the parser
+        assert_eq!(moved(r#"{"mappings":"","sourcesContent":null}"#), None);
+        assert_eq!(moved(r#"{"sourcesContent":[1]}"#), None);
+        assert_eq!(moved(r#"{"x":{"sourcesContent":[]}}"#), None);
+        assert_eq!(moved(r#"{"mappings":""}"#), None);
+        assert_eq!(moved(r#"["#), None);
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert!(matches!(unescape(b"let a"), Some(Cow::Borrowed("let a"))));
+        assert_eq!(
+            unescape(br#"\"\\\/\b\f\n\r\t\u00e9\ud83e\udd80"#).unwrap(),
+            "\"\\/\u{8}\u{c}\n\r\t\u{e9}🦀"
+        );
+        assert_eq!(unescape(b"a\nb"), None);
+        assert_eq!(unescape(br#"\x"#), None);
+        assert_eq!(unescape(br#"\u00"#), None);
+        assert_eq!(unescape(br#"\ud83e"#), None);
+        assert_eq!(unescape(br#"\udd80"#), None);
+        assert_eq!(unescape(b"\xff"), None);
+    }
+}
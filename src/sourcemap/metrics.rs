@@ -0,0 +1,31 @@
+use crate::{BorrowedSourceMap, ParseResult};
+use std::time::{Duration, Instant};
+
+/// Metrics collected while parsing a source map.
+///
+/// See [from_slice_with_metrics](BorrowedSourceMap::from_slice_with_metrics).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ParseMetrics {
+    /// The size of the input JSON in bytes.
+    pub bytes: usize,
+    /// The number of decoded mappings.
+    pub mappings: usize,
+    /// The time taken to parse.
+    pub elapsed: Duration,
+}
+
+impl<'a> BorrowedSourceMap<'a> {
+    /// Creates a new `BorrowedSourceMap` from a JSON buffer slice like
+    /// [from_slice](Self::from_slice), and measures the parsing.
+    pub fn from_slice_with_metrics(json: &'a mut [u8]) -> ParseResult<(Self, ParseMetrics)> {
+        let bytes = json.len();
+        let start = Instant::now();
+        let sm = Self::from_slice(json)?;
+        let metrics = ParseMetrics {
+            bytes,
+            mappings: sm.mappings.len(),
+            elapsed: start.elapsed(),
+        };
+        Ok((sm, metrics))
+    }
+}
@@ -1,11 +1,43 @@
 mod borrowed;
+mod classify;
+mod concat;
+mod diff;
+mod input;
+mod normalize;
+mod options;
 mod owned;
 mod raw;
+mod resolved;
+mod shared;
+mod split;
+mod utf16;
 
 pub use borrowed::*;
+pub use classify::*;
+pub use diff::*;
+pub use input::*;
+pub use options::*;
 pub use owned::*;
+pub use resolved::*;
+pub use shared::*;
 
 #[cfg(feature = "builder")]
 mod builder;
 #[cfg(feature = "builder")]
 pub use builder::*;
+
+#[cfg(feature = "dedup")]
+mod dedup;
+
+#[cfg(feature = "lazy-content")]
+mod lazy;
+#[cfg(feature = "lazy-content")]
+pub use lazy::*;
+
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+
+#[cfg(feature = "serde")]
+mod json_value;
@@ -0,0 +1,390 @@
+use crate::BorrowedSourceMap;
+use std::borrow::Cow;
+
+impl BorrowedSourceMap<'_> {
+    /// Canonicalizes the `sources` like most devtools do before displaying them.
+    ///
+    /// For `webpack://`, `file://`, `http://` and `https://` urls, as well as plain paths:
+    /// - percent-encoded characters are decoded, except `/` (`%2F`), `?` (`%3F`) and `#` (`%23`)
+    ///   which would change the meaning of the path, and `%` (`%25`) which would be decoded
+    ///   again on the next normalization, and unless the decoded bytes are not valid UTF-8,
+    /// - `.` segments are removed, and `..` segments are collapsed with the preceding segment.
+    ///   Leading `..` segments are dropped from absolute paths, since the root has no parent,
+    ///   but kept in relative paths and `webpack://` urls since it's unknown where they point to.
+    ///
+    /// The scheme, the host, and the query or fragment are kept intact.
+    /// Sources with other schemes, like `data:`, are left untouched.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let mut sm = SourceMap::from(
+    ///     br#"{"version":3,"sources":["webpack:///./src/../lib/a%20b.js"],"mappings":""}"#.to_vec(),
+    /// ).unwrap();
+    /// sm.normalize_source_urls();
+    /// assert_eq!(sm.source_at(0), Some("webpack:///lib/a b.js"));
+    /// ```
+    pub fn normalize_source_urls(&mut self) {
+        self.map_sources(normalize_source_url);
+    }
+
+    /// Returns the source at the given id, resolved against the `source_root`.
+    ///
+    /// Unlike a naive concatenation, a `/` is inserted between the root and a relative source
+    /// if missing, sources which are urls or absolute paths are returned as is, and an absolute
+    /// path is resolved against the scheme and the host if the root is an url, so is a
+    /// protocol-relative url like `//host/a.js` against the scheme. The `.` and `..` segments
+    /// of the result are collapsed like [normalize_source_urls](Self::normalize_source_urls)
+    /// does, but nothing is percent-decoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(
+    ///     br#"{"version":3,"sourceRoot":"http://x/","sources":["a.js","/b.js"],"mappings":""}"#.to_vec(),
+    /// ).unwrap();
+    /// assert_eq!(sm.resolved_source(0).as_deref(), Some("http://x/a.js"));
+    /// assert_eq!(sm.resolved_source(1).as_deref(), Some("http://x/b.js"));
+    /// ```
+    pub fn resolved_source(&self, source_id: u32) -> Option<Cow<'_, str>> {
+        let source = self.source_at(source_id)?;
+        let root = match self.source_root.as_deref() {
+            Some(root) if !root.is_empty() => root,
+            _ => return Some(Cow::Borrowed(source)),
+        };
+        if has_scheme(source) || has_drive_letter(source) {
+            return Some(Cow::Borrowed(source));
+        }
+        if source.starts_with("//") {
+            // a protocol-relative url, not an absolute path
+            return Some(match root.find("://") {
+                Some(idx) => Cow::Owned(format!("{}:{}", &root[..idx], source)),
+                None => Cow::Borrowed(source),
+            });
+        }
+        let joined = if source.starts_with('/') {
+            // only an url root has something to resolve an absolute path against
+            match root.find("://") {
+                Some(idx) => {
+                    let host_end = root[idx + 3..]
+                        .find('/')
+                        .map_or(root.len(), |i| idx + 3 + i);
+                    format!("{}{}", &root[..host_end], source)
+                }
+                None => return Some(Cow::Borrowed(source)),
+            }
+        } else {
+            let source = source.strip_prefix("./").unwrap_or(source);
+            let separator = if root.ends_with('/') { "" } else { "/" };
+            format!("{}{}{}", root, separator, source)
+        };
+        Some(match split_url(&joined) {
+            Some((prefix, path, suffix)) => {
+                match collapse_dot_segments(path, clamp_at_root(prefix)) {
+                    Cow::Borrowed(_) => Cow::Owned(joined),
+                    Cow::Owned(path) => Cow::Owned(format!("{}{}{}", prefix, path, suffix)),
+                }
+            }
+            None => Cow::Owned(joined),
+        })
+    }
+}
+
+const SCHEMES: &[&str] = &["webpack", "file", "http", "https"];
+
+fn normalize_source_url(source: &str) -> Cow<'_, str> {
+    let Some((prefix, path, suffix)) = split_url(source) else {
+        return Cow::Borrowed(source);
+    };
+    let decoded = percent_decode(path);
+    let normalized = collapse_dot_segments(&decoded, clamp_at_root(prefix));
+    if normalized == path {
+        Cow::Borrowed(source)
+    } else {
+        Cow::Owned(format!("{}{}{}", prefix, normalized, suffix))
+    }
+}
+
+/// Splits the source into the scheme and host, the path, and the query and fragment, if it's
+/// a plain path or an url with one of the [SCHEMES]. Returns `None` for other schemes, like
+/// `data:`, which are not paths.
+fn split_url(source: &str) -> Option<(&str, &str, &str)> {
+    match source.find("://") {
+        Some(idx) => {
+            let scheme = &source[..idx];
+            if !SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(scheme)) {
+                return None;
+            }
+            let path_start = source[idx + 3..]
+                .find('/')
+                .map_or(source.len(), |i| idx + 3 + i);
+            let path_end = source[path_start..]
+                .find(['?', '#'])
+                .map_or(source.len(), |i| path_start + i);
+            Some((
+                &source[..path_start],
+                &source[path_start..path_end],
+                &source[path_end..],
+            ))
+        }
+        None if has_scheme(source) => None,
+        None => Some(("", source, "")),
+    }
+}
+
+/// Checks if the `..` segments above the root should be dropped from the path after `prefix`,
+/// which webpack resolves against the context directory instead, which may have parents.
+fn clamp_at_root(prefix: &str) -> bool {
+    !prefix
+        .get(..10)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("webpack://"))
+}
+
+/// Checks if the source starts with an url scheme, other than a Windows drive letter.
+fn has_scheme(source: &str) -> bool {
+    match source.find(':') {
+        Some(idx) if idx > 1 => {
+            let scheme = &source.as_bytes()[..idx];
+            scheme[0].is_ascii_alphabetic()
+                && scheme
+                    .iter()
+                    .all(|&b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+        }
+        _ => false,
+    }
+}
+
+/// Checks if the source starts with a Windows drive letter, e.g. `C:\\` or `C:/`.
+fn has_drive_letter(source: &str) -> bool {
+    matches!(source.as_bytes(), [letter, b':', b'\\' | b'/', ..] if letter.is_ascii_alphabetic())
+}
+
+fn percent_decode(path: &str) -> Cow<'_, str> {
+    if !path.contains('%') {
+        return Cow::Borrowed(path);
+    }
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            let hex = |b: u8| (b as char).to_digit(16);
+            if let (Some(hi), Some(lo)) = (hex(bytes[idx + 1]), hex(bytes[idx + 2])) {
+                let byte = (hi * 16 + lo) as u8;
+                if !matches!(byte, b'/' | b'?' | b'#' | b'%') {
+                    decoded.push(byte);
+                    idx += 3;
+                    continue;
+                }
+            }
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+    match String::from_utf8(decoded) {
+        Ok(decoded) => Cow::Owned(decoded),
+        Err(_) => Cow::Borrowed(path),
+    }
+}
+
+/// Removes the `.` segments and collapses the `..` segments of the path, dropping those that
+/// would go above the root of an absolute path if `clamp_at_root`.
+fn collapse_dot_segments(path: &str, clamp_at_root: bool) -> Cow<'_, str> {
+    if !path.split('/').any(|s| s == "." || s == "..") {
+        return Cow::Borrowed(path);
+    }
+    let is_absolute = path.starts_with('/');
+    let is_dir = path.ends_with('/') || path.ends_with("/.") || path.ends_with("/..");
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/').skip(is_absolute as usize) {
+        match segment {
+            "." => {}
+            ".." => match segments.last() {
+                Some(&last) if last != ".." => {
+                    segments.pop();
+                }
+                None if is_absolute && clamp_at_root => {}
+                _ => segments.push(".."),
+            },
+            _ => segments.push(segment),
+        }
+    }
+    // the trailing empty segment of a directory is restored below
+    if segments.last() == Some(&"") {
+        segments.pop();
+    }
+
+    if !is_absolute && segments.is_empty() {
+        return Cow::Borrowed(".");
+    }
+
+    let mut result = String::with_capacity(path.len());
+    if is_absolute {
+        result.push('/');
+    }
+    result.push_str(&segments.join("/"));
+    if is_dir && !segments.is_empty() {
+        result.push('/');
+    }
+    Cow::Owned(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_source_url;
+    use crate::SourceMap;
+    use std::borrow::Cow;
+
+    fn resolve(source_root: Option<&str>, source: &str) -> String {
+        let mut sm =
+            SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":""}"#.to_vec()).unwrap();
+        *sm.source_root_mut() = source_root.map(|root| Cow::Owned(root.to_owned()));
+        sm.sources_mut()[0] = Some(Cow::Owned(source.to_owned()));
+        sm.resolved_source(0).unwrap().into_owned()
+    }
+
+    #[test]
+    fn test_resolved_source_url_root() {
+        assert_eq!(resolve(Some("http://x/"), "a.js"), "http://x/a.js");
+        assert_eq!(resolve(Some("http://x"), "a.js"), "http://x/a.js");
+        assert_eq!(
+            resolve(Some("http://x/lib/"), "./a.js"),
+            "http://x/lib/a.js"
+        );
+        assert_eq!(resolve(Some("http://x/lib/"), "/a.js"), "http://x/a.js");
+        assert_eq!(resolve(Some("http://x"), "/a.js"), "http://x/a.js");
+        assert_eq!(
+            resolve(Some("http://x/"), "https://y/a.js"),
+            "https://y/a.js"
+        );
+        assert_eq!(
+            resolve(Some("webpack:///"), "src/a.js"),
+            "webpack:///src/a.js"
+        );
+        assert_eq!(
+            resolve(Some("http://x/lib/"), "../a.js?v=1"),
+            "http://x/a.js?v=1"
+        );
+        assert_eq!(resolve(Some("http://x/lib"), "../../a.js"), "http://x/a.js");
+        assert_eq!(
+            resolve(Some("webpack:///src"), "../../a.js"),
+            "webpack:///../a.js"
+        );
+        // scheme-relative, not an absolute path
+        assert_eq!(
+            resolve(Some("https://x/lib/"), "//y/a.js"),
+            "https://y/a.js"
+        );
+        assert_eq!(resolve(Some("/project/"), "//y/a.js"), "//y/a.js");
+    }
+
+    #[test]
+    fn test_resolved_source_path_root() {
+        assert_eq!(resolve(Some("src"), "a.js"), "src/a.js");
+        assert_eq!(
+            resolve(Some("/project/src/"), "lib/a.js"),
+            "/project/src/lib/a.js"
+        );
+        assert_eq!(resolve(Some("/project/src/"), "/abs/a.js"), "/abs/a.js");
+        assert_eq!(resolve(Some("C:\\src"), "a.js"), "C:\\src/a.js");
+        assert_eq!(resolve(Some("/src"), "C:\\a.js"), "C:\\a.js");
+        assert_eq!(resolve(Some(""), "a.js"), "a.js");
+        assert_eq!(resolve(None, "a.js"), "a.js");
+        assert_eq!(resolve(Some("src/lib"), "../a.js"), "src/a.js");
+        assert_eq!(resolve(Some("src"), "../../a.js"), "../a.js");
+        assert_eq!(resolve(Some("/project/src"), "../../../a.js"), "/a.js");
+    }
+
+    #[test]
+    fn test_normalize_webpack() {
+        assert_eq!(
+            normalize_source_url("webpack:///./src/index.js"),
+            "webpack:///src/index.js"
+        );
+        assert_eq!(
+            normalize_source_url("webpack://my-app/./src/./utils/../index.js?3f2a"),
+            "webpack://my-app/src/index.js?3f2a"
+        );
+        // leading `..` is kept
+        assert_eq!(
+            normalize_source_url("webpack:///../node_modules/lodash/./index.js"),
+            "webpack:///../node_modules/lodash/index.js"
+        );
+    }
+
+    #[test]
+    fn test_normalize_file() {
+        assert_eq!(
+            normalize_source_url("file:///home/user/my%20project/src/../lib/a.js"),
+            "file:///home/user/my project/lib/a.js"
+        );
+        assert_eq!(
+            normalize_source_url("file:///C:/a/b/../../c.js"),
+            "file:///C:/c.js"
+        );
+        assert_eq!(normalize_source_url("file:///a/../../b.js"), "file:///b.js");
+    }
+
+    #[test]
+    fn test_normalize_http() {
+        assert_eq!(
+            normalize_source_url("https://example.com/static/./js/../src/%E4%BD%A0%E5%A5%BD.js#L1"),
+            "https://example.com/static/src/你好.js#L1"
+        );
+        assert_eq!(
+            normalize_source_url("HTTP://example.com/a/b/./"),
+            "HTTP://example.com/a/b/"
+        );
+        // `%2F`, `%3F`, `%23` and `%25` are not decoded, and the host is kept
+        assert_eq!(
+            normalize_source_url("http://example.com/a%2Fb/c.js"),
+            "http://example.com/a%2Fb/c.js"
+        );
+        assert_eq!(
+            normalize_source_url("http://example.com/./a%3Fb%23c%2541%20.js?q#f"),
+            "http://example.com/a%3Fb%23c%2541 .js?q#f"
+        );
+        assert_eq!(
+            normalize_source_url("http://example.com/../a.js"),
+            "http://example.com/a.js"
+        );
+        assert_eq!(
+            normalize_source_url("http://example.com"),
+            "http://example.com"
+        );
+    }
+
+    #[test]
+    fn test_normalize_plain() {
+        assert_eq!(normalize_source_url("./src/../a.js"), "a.js");
+        assert_eq!(normalize_source_url("../../a/./b.js"), "../../a/b.js");
+        // the root has no parent
+        assert_eq!(normalize_source_url("/a/../../b.js"), "/b.js");
+        assert_eq!(normalize_source_url("/../a/./b.js"), "/a/b.js");
+        assert_eq!(normalize_source_url("/.."), "/");
+        assert_eq!(normalize_source_url("a/.."), ".");
+        assert_eq!(normalize_source_url("C:\\a\\..\\b.js"), "C:\\a\\..\\b.js");
+        assert_eq!(normalize_source_url("C:/a/../b.js"), "C:/b.js");
+    }
+
+    #[test]
+    fn test_normalize_untouched() {
+        for source in [
+            "data:text/plain,a/../b",
+            "foo://a/../b",
+            "webpack:///src/a.js",
+            "a%ZZ.js",
+            "a%3F%23%2F%25.js",
+            // invalid UTF-8 after decoding
+            "a%FF.js",
+        ] {
+            let normalized = normalize_source_url(source);
+            assert!(
+                std::ptr::eq(normalized.as_ref(), source),
+                "{} -> {}",
+                source,
+                normalized
+            );
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::VlqAlphabet;
+
+/// Options to customize the parsing of source maps.
+///
+/// See [from_slice_with_options](crate::BorrowedSourceMap::from_slice_with_options). The options
+/// apply to an index map and each of its sections alike, except that the `version` of the sections
+/// is not checked.
+///
+/// # Example
+/// ```
+/// # use sora::ParseOptions;
+/// let mut options = ParseOptions::default();
+/// options.max_mappings = Some(1_000_000);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// The maximum number of mappings allowed, exceeding which the parsing fails with
+    /// [TooManyMappings](crate::ParseError::TooManyMappings). The end sentinels, see
+    /// [insert_end_sentinel](Self::insert_end_sentinel), don't count.
+    ///
+    /// This caps the resource usage when parsing untrusted source maps. Unlimited by default.
+    pub max_mappings: Option<usize>,
+    /// The base64 alphabet used to decode `mappings`, see [VlqAlphabet].
+    pub alphabet: VlqAlphabet,
+    /// Whether to append a mapping without source information at the start of the last
+    /// generated line, when `mappings` ends with empty lines, e.g. `"AAAA;"`. Enabled by default.
+    ///
+    /// The sentinel marks the end of the map, so that finding on the trailing lines results in
+    /// a mapping without source information. When disabled, the decoded mappings are exactly
+    /// the segments of `mappings`, and finding on the trailing lines results in the last mapping
+    /// of the previous lines instead.
+    pub insert_end_sentinel: bool,
+    /// Whether to accept a source map wrapped in a single-element array, e.g. `[{...}]`, as
+    /// emitted by a few pipelines. Disabled by default.
+    ///
+    /// An array with any other number of elements fails with
+    /// [WrappedArrayLength](crate::ParseError::WrappedArrayLength).
+    pub unwrap_array: bool,
+    /// Whether to coerce the entries of `sources` which are neither a string nor `null`, as
+    /// emitted by a few broken generators, to `None`. Each coerced entry is reported as a
+    /// [InvalidSource](crate::ParseWarning::InvalidSource) warning. Disabled by default.
+    ///
+    /// When disabled, such an entry fails with [InvalidSource](crate::ParseError::InvalidSource).
+    pub coerce_invalid_sources: bool,
+    /// Whether to accept a `sourcesContent` whose length differs from `sources`, padding it with
+    /// `None` or dropping the extra entries. The mismatch is reported as a
+    /// [MismatchSourcesContent](crate::ParseWarning::MismatchSourcesContent) warning.
+    /// Disabled by default.
+    ///
+    /// When disabled, such a map fails with
+    /// [MismatchSourcesContent](crate::ParseError::MismatchSourcesContent).
+    pub resize_sources_content: bool,
+    /// Whether to accept a map without `version`, as version 3. Disabled by default.
+    pub accept_missing_version: bool,
+    /// Whether to drop the entries of `ignoreList` which reference unknown sources, instead of
+    /// retaining them. Either way, they are reported as
+    /// [IgnoreListOutOfRange](crate::ParseWarning::IgnoreListOutOfRange) warnings.
+    /// Disabled by default.
+    ///
+    /// For an index map, the out-of-range entries of the sections are always dropped, since
+    /// their ids rebased onto the flattened `sources` may point to the sources of later sections.
+    /// The ids of the warnings are rebased as well, saturating at `u32::MAX`.
+    #[cfg(feature = "ignore_list")]
+    pub drop_invalid_ignore_list: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_mappings: None,
+            alphabet: VlqAlphabet::default(),
+            insert_end_sentinel: true,
+            unwrap_array: false,
+            coerce_invalid_sources: false,
+            resize_sources_content: false,
+            accept_missing_version: false,
+            #[cfg(feature = "ignore_list")]
+            drop_invalid_ignore_list: false,
+        }
+    }
+}
+
+/// The leniency quirks of popular source map consumers, as a single knob to parse maps the
+/// same way as the target runtime does.
+///
+/// See [from_slice_with_mode](crate::BorrowedSourceMap::from_slice_with_mode), and the
+/// conversion into [ParseOptions] for the details of each mode.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CompatMode {
+    /// Follows the specification, same as [ParseOptions::default].
+    #[default]
+    Strict,
+    /// Like Chrome DevTools, which pads or truncates `sourcesContent` to the `sources`, drops
+    /// the `ignoreList` entries of unknown sources, and accepts a map without `version`.
+    Chrome,
+    /// Like Node.js with `--enable-source-maps`, which rejects a mismatched `sourcesContent`,
+    /// but accepts a map without `version`.
+    Node,
+}
+
+impl From<CompatMode> for ParseOptions {
+    fn from(mode: CompatMode) -> Self {
+        let strict = Self::default();
+        match mode {
+            CompatMode::Strict => strict,
+            CompatMode::Chrome => Self {
+                resize_sources_content: true,
+                accept_missing_version: true,
+                #[cfg(feature = "ignore_list")]
+                drop_invalid_ignore_list: true,
+                ..strict
+            },
+            CompatMode::Node => Self {
+                accept_missing_version: true,
+                ..strict
+            },
+        }
+    }
+}
+
+/// Options to customize the serialization of source maps.
+///
+/// See [write_with_options](crate::BorrowedSourceMap::write_with_options).
+///
+/// # Example
+/// ```
+/// # use sora::WriteOptions;
+/// // a fully self-contained map
+/// let mut options = WriteOptions::default();
+/// options.inline_sources = true;
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct WriteOptions {
+    /// Whether the map must be self-contained, i.e. the content of every source is inlined in
+    /// `sourcesContent`. If any content is missing, the writing fails with
+    /// [InvalidData](std::io::ErrorKind::InvalidData) before anything is written.
+    ///
+    /// When disabled, `sourcesContent` is written as is, including the missing entries as `null`.
+    pub inline_sources: bool,
+    /// Whether to omit `sourcesContent`, producing a reference-only map whose consumers load
+    /// the sources by their paths. It conflicts with `inline_sources`.
+    pub exclude_sources_content: bool,
+    /// Whether to also write `ignoreList` as `x_google_ignoreList`, the name used before it was
+    /// standardized, for consumers that only recognize the legacy key.
+    #[cfg(feature = "ignore_list")]
+    pub legacy_ignore_list: bool,
+}
+
+/// The style of the `sourceMappingURL` comment, see
+/// [to_inline_comment](crate::BorrowedSourceMap::to_inline_comment).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum CommentStyle {
+    /// `//# sourceMappingURL=`, as defined by the specification.
+    #[default]
+    Modern,
+    /// `//@ sourceMappingURL=`, recognized by old engines which predate the specification.
+    ///
+    /// It's deprecated since `//@` conflicts with the conditional compilation of old IE.
+    Legacy,
+}
+
+impl CommentStyle {
+    /// Returns the start of the comment, up to and including `=`.
+    #[inline]
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            Self::Modern => "//# sourceMappingURL=",
+            Self::Legacy => "//@ sourceMappingURL=",
+        }
+    }
+}
@@ -16,9 +16,50 @@ impl SourceMap {
 }
 
 impl BorrowedSourceMap<'_> {
+    /// Creates a [BorrowedSourceMap] that borrows all its internal strings from this one.
+    ///
+    /// This is the dual of [into_owned](BorrowedSourceMap::into_owned): no string is cloned,
+    /// but it's not free either, since the mappings are copied and the vecs of `names`,
+    /// `sources` and `sources_content` are allocated anew, which takes O(n) time and memory in
+    /// the number of mappings and entries.
+    ///
+    /// It's only needed to pass a map by value. A reference needs no conversion, since
+    /// `&SourceMap` coerces to `&BorrowedSourceMap<'a>` for any `'a`.
+    pub fn as_borrowed(&self) -> BorrowedSourceMap<'_> {
+        #[allow(clippy::ptr_arg)]
+        fn as_borrowed<'b>(i: &'b Cow<'_, str>) -> Cow<'b, str> {
+            Cow::Borrowed(i)
+        }
+
+        BorrowedSourceMap {
+            file: self.file.as_ref().map(as_borrowed),
+            mappings: self.mappings.clone(),
+            names: self.names.iter().map(as_borrowed).collect(),
+            source_root: self.source_root.as_ref().map(as_borrowed),
+            sources: self
+                .sources
+                .iter()
+                .map(|n| n.as_ref().map(as_borrowed))
+                .collect(),
+            sources_content: self
+                .sources_content
+                .iter()
+                .map(|n| n.as_ref().map(as_borrowed))
+                .collect(),
+            #[cfg(feature = "ignore_list")]
+            ignore_list: self.ignore_list.clone(),
+        }
+    }
+
     /// Convert a [BorrowedSourceMap] into a [SourceMap] that owns all its internal strings.
+    ///
+    /// Only the borrowed strings are cloned: the owned ones are moved without reallocation,
+    /// and so are the vecs, since `Cow<'_, str>` and `Cow<'static, str>` share the same layout.
+    /// So converting a map which is already owned, e.g. a [SourceMap], costs a single walk
+    /// over the strings.
     pub fn into_owned(self) -> SourceMap {
         fn into_owned(i: Cow<'_, str>) -> Cow<'static, str> {
+            // `Cow::into_owned` moves the string out of `Cow::Owned`
             Cow::Owned(i.into_owned())
         }
 
@@ -1,19 +1,103 @@
+use crate::sourcemap::ParseOptions;
+use crate::{ParseError, ParseResult};
+use simd_json::{Node, StaticNode};
+use simd_json_derive::Deserialize;
+
 #[derive(Debug, simd_json_derive::Deserialize)]
 #[simd_json(rename_all = "camelCase")]
 pub(crate) struct RawSourceMap<'a> {
-    pub version: Option<u32>,
+    // a float to accept `3.0`, which is emitted by some JSON serializers
+    pub version: Option<f64>,
     pub file: Option<&'a str>,
-    pub sources: Option<Vec<Option<&'a str>>>,
+    pub sources: Option<Vec<RawSource<'a>>>,
     pub source_root: Option<&'a str>,
     pub sources_content: Option<Vec<Option<&'a str>>>,
     pub names: Option<Vec<&'a str>>,
     pub mappings: Option<&'a str>,
     #[cfg(feature = "ignore_list")]
     pub ignore_list: Option<Vec<u32>>,
+    // the legacy name of `ignoreList`, emitted by tools before it was standardized
+    #[cfg(feature = "ignore_list")]
+    #[simd_json(rename = "x_google_ignoreList")]
+    pub x_google_ignore_list: Option<Vec<u32>>,
     #[cfg(feature = "index-map")]
     pub sections: Option<Vec<RawSection<'a>>>,
 }
 
+/// An entry of `sources`, which is either a string or `null` in a valid source map.
+///
+/// Other values are kept as [Invalid](RawSource::Invalid) instead of failing the deserialization,
+/// so they can be coerced to `null` with [ParseOptions::coerce_invalid_sources].
+#[derive(Debug)]
+pub(crate) enum RawSource<'a> {
+    Valid(Option<&'a str>),
+    Invalid,
+}
+
+impl<'input> Deserialize<'input> for RawSource<'input> {
+    fn from_tape(tape: &mut simd_json_derive::Tape<'input>) -> simd_json::Result<Self>
+    where
+        Self: Sized + 'input,
+    {
+        match tape.peek() {
+            Some(Node::Array { count, .. } | Node::Object { count, .. }) => {
+                // skip the container along with its nested nodes
+                let count = *count;
+                tape.nth(count);
+                Ok(Self::Invalid)
+            }
+            Some(Node::Static(StaticNode::Null) | Node::String(_)) | None => {
+                Option::<&'input str>::from_tape(tape).map(Self::Valid)
+            }
+            Some(Node::Static(_)) => {
+                tape.next();
+                Ok(Self::Invalid)
+            }
+        }
+    }
+}
+
+impl<'a> RawSourceMap<'a> {
+    /// Merges `ignoreList` with the entries of `x_google_ignoreList` not in it.
+    #[cfg(feature = "ignore_list")]
+    pub fn merge_ignore_lists(
+        ignore_list: Option<Vec<u32>>,
+        legacy: Option<Vec<u32>>,
+    ) -> Option<Vec<u32>> {
+        match (ignore_list, legacy) {
+            (Some(mut ignore_list), Some(legacy)) => {
+                for source_id in legacy {
+                    if !ignore_list.contains(&source_id) {
+                        ignore_list.push(source_id);
+                    }
+                }
+                Some(ignore_list)
+            }
+            (ignore_list, legacy) => ignore_list.or(legacy),
+        }
+    }
+
+    /// Parses the JSON with the lenient handling enabled in the `options`.
+    pub fn from_slice_with_options(
+        json: &'a mut [u8],
+        options: &ParseOptions,
+    ) -> ParseResult<Self> {
+        let is_array = || {
+            json.iter()
+                .find(|b| !b.is_ascii_whitespace())
+                .is_some_and(|&b| b == b'[')
+        };
+        if options.unwrap_array && is_array() {
+            let mut maps = Vec::<RawSourceMap>::from_slice(json)?;
+            if maps.len() != 1 {
+                return Err(ParseError::WrappedArrayLength(maps.len()));
+            }
+            return Ok(maps.pop().unwrap());
+        }
+        Ok(Self::from_slice(json)?)
+    }
+}
+
 #[cfg(feature = "index-map")]
 #[derive(Debug, simd_json_derive::Deserialize)]
 pub(crate) struct RawSectionOffset {
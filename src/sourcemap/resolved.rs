@@ -0,0 +1,89 @@
+use crate::{BorrowedSourceMap, Mapping, Position};
+
+/// `ResolvedMapping` is a [Mapping] with its source and name ids resolved to the strings of
+/// the map, see [resolved_mappings](BorrowedSourceMap::resolved_mappings).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ResolvedMapping<'s> {
+    pub generated: Position,
+    /// The source path, as is in `sources`. It's `None` if the mapping has no source
+    /// information, or the source is `null`.
+    pub source: Option<&'s str>,
+    /// The original position, or `None` if the mapping has no source information.
+    pub original: Option<Position>,
+    pub name: Option<&'s str>,
+}
+
+impl BorrowedSourceMap<'_> {
+    /// Returns an iterator over the mappings with their sources and names resolved, which is
+    /// the view most consumers need, without indexing `sources` and `names` by hand.
+    ///
+    /// The source paths are not joined with the `source_root`, see
+    /// [resolved_source](Self::resolved_source) for that.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::{Position, SourceMap};
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"names":["x"],"mappings":"AAAA,EAAEA"}"#.to_vec()).unwrap();
+    /// let last = sm.resolved_mappings().last().unwrap();
+    /// assert_eq!(last.generated, Position::new(0, 2));
+    /// assert_eq!(last.source, Some("a.js"));
+    /// assert_eq!(last.original, Some(Position::new(0, 2)));
+    /// assert_eq!(last.name, Some("x"));
+    /// ```
+    pub fn resolved_mappings(&self) -> impl Iterator<Item = ResolvedMapping<'_>> + '_ {
+        self.mappings.iter().map(|mapping| self.resolve(mapping))
+    }
+
+    /// Explains the mapping of a generated position in a line of text for debugging, e.g.
+    /// `generated 10:12 maps to ../project/sum.ts:2:15 (name 'total')`.
+    ///
+    /// Like [find_mapping](Self::find_mapping), the closest preceding mapping is used.
+    /// The positions are 0-based, and a `null` source is shown as `<null>`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"names":["x"],"mappings":"CAAAA"}"#.to_vec()).unwrap();
+    /// assert_eq!(sm.explain((0, 2)), "generated 0:2 maps to a.js:0:0 (name 'x')");
+    /// assert_eq!(sm.explain((0, 0)), "no mapping at 0:0");
+    /// ```
+    pub fn explain<P>(&self, pos: P) -> String
+    where
+        P: Into<Position>,
+    {
+        let pos = pos.into();
+        let Some(mapping) = self.find_mapping(pos) else {
+            return format!("no mapping at {}:{}", pos.line, pos.column);
+        };
+        let resolved = self.resolve(&mapping);
+        let Some(original) = resolved.original else {
+            return format!(
+                "generated {}:{} has no original source",
+                pos.line, pos.column
+            );
+        };
+        let mut explanation = format!(
+            "generated {}:{} maps to {}:{}:{}",
+            pos.line,
+            pos.column,
+            resolved.source.unwrap_or("<null>"),
+            original.line,
+            original.column
+        );
+        if let Some(name) = resolved.name {
+            explanation.push_str(&format!(" (name '{}')", name));
+        }
+        explanation
+    }
+
+    /// Resolves the source and name ids of the mapping.
+    pub(crate) fn resolve(&self, mapping: &Mapping) -> ResolvedMapping<'_> {
+        let source_info = mapping.source_info();
+        ResolvedMapping {
+            generated: mapping.generated(),
+            source: source_info.and_then(|info| self.source_at(info.id)),
+            original: source_info.map(|info| info.position),
+            name: mapping.name_id().and_then(|id| self.name_at(id)),
+        }
+    }
+}
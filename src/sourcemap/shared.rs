@@ -0,0 +1,65 @@
+use crate::{MappingFinder, SourceMap};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// `SharedSourceMap` is a cheaply cloneable [SourceMap] behind an [Arc], to be queried from
+/// many threads, e.g. in symbolication servers.
+///
+/// The map itself is immutable and `Sync`, but a [MappingFinder] keeps its state in a `Cell`,
+/// so it's `!Sync`: each thread should create its own finder from the shared map, which is cheap.
+///
+/// # Example
+/// ```
+/// # use sora::{SharedSourceMap, SourceMap};
+/// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC"}"#.to_vec()).unwrap();
+/// let shared = SharedSourceMap::new(sm);
+/// let handle = std::thread::spawn({
+///     let shared = shared.clone();
+///     move || shared.finder().find_mapping((0, 1))
+/// });
+/// assert_eq!(handle.join().unwrap(), shared.find_mapping((0, 1)));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SharedSourceMap(Arc<SourceMap>);
+
+impl SharedSourceMap {
+    #[inline]
+    pub fn new(source_map: SourceMap) -> Self {
+        Self(Arc::new(source_map))
+    }
+
+    /// Creates a [MappingFinder] for the calling thread.
+    #[inline]
+    pub fn finder(&self) -> MappingFinder {
+        self.0.finder()
+    }
+
+    /// Returns the underlying [Arc].
+    #[inline]
+    pub fn into_inner(self) -> Arc<SourceMap> {
+        self.0
+    }
+}
+
+impl Deref for SharedSourceMap {
+    type Target = SourceMap;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<SourceMap> for SharedSourceMap {
+    #[inline]
+    fn from(source_map: SourceMap) -> Self {
+        Self::new(source_map)
+    }
+}
+
+impl From<Arc<SourceMap>> for SharedSourceMap {
+    #[inline]
+    fn from(source_map: Arc<SourceMap>) -> Self {
+        Self(source_map)
+    }
+}
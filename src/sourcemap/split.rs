@@ -0,0 +1,68 @@
+use crate::{BorrowedSourceMap, Mapping, Mappings};
+use std::collections::HashMap;
+
+impl<'a> BorrowedSourceMap<'a> {
+    /// Splits the source map into one source map per source.
+    ///
+    /// For each source id, the returned source map contains only that source (re-indexed to
+    /// source id 0), the mappings referencing it and the names they reference.
+    /// Generated positions are preserved, and `file` and `source_root` are kept.
+    pub fn split_by_source(&self) -> Vec<(u32, BorrowedSourceMap<'a>)> {
+        let sources_len = self.sources.len();
+        let mut mappings = vec![Vec::new(); sources_len];
+        // (names, old name id -> new name id) for each source
+        let mut names = vec![(Vec::new(), HashMap::new()); sources_len];
+
+        for mapping in self.mappings.iter() {
+            let Some(source_info) = mapping.source_info() else {
+                continue;
+            };
+            let Some(source_mappings) = mappings.get_mut(source_info.id as usize) else {
+                continue;
+            };
+            let generated = mapping.generated();
+            let position = source_info.position;
+            let mut split = Mapping::new(generated.line, generated.column).with_source(
+                0,
+                position.line,
+                position.column,
+            );
+            if let Some(name_id) = mapping.name_id() {
+                if let Some(name) = self.names.get(name_id as usize) {
+                    let (source_names, name_ids) = &mut names[source_info.id as usize];
+                    let new_name_id = *name_ids.entry(name_id).or_insert_with(|| {
+                        source_names.push(name.clone());
+                        source_names.len() as u32 - 1
+                    });
+                    split = split.with_name(new_name_id);
+                }
+            }
+            source_mappings.push(split);
+        }
+
+        mappings
+            .into_iter()
+            .zip(names)
+            .enumerate()
+            .map(|(idx, (mappings, (names, _)))| {
+                let source_id = idx as u32;
+                let sm = BorrowedSourceMap {
+                    file: self.file.clone(),
+                    // the mappings are in order since they are picked from ordered mappings
+                    mappings: Mappings(mappings),
+                    names,
+                    source_root: self.source_root.clone(),
+                    sources: vec![self.sources[idx].clone()],
+                    sources_content: vec![self.sources_content.get(idx).cloned().flatten()],
+                    #[cfg(feature = "ignore_list")]
+                    ignore_list: if self.ignore_list.contains(&source_id) {
+                        vec![0]
+                    } else {
+                        vec![]
+                    },
+                };
+                (source_id, sm)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{BorrowedSourceMap, Mapping, Position};
+
+impl BorrowedSourceMap<'_> {
+    /// Returns whether the content of the source is pure ASCII, in which case UTF-16 and UTF-8
+    /// columns are the same and no conversion is needed. Returns `None` if the source has no
+    /// content.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(
+    ///     r#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["let a","let é"],"mappings":""}"#.into(),
+    /// ).unwrap();
+    /// assert_eq!(sm.content_is_ascii(0), Some(true));
+    /// assert_eq!(sm.content_is_ascii(1), Some(false));
+    /// ```
+    #[inline]
+    pub fn content_is_ascii(&self, source_id: u32) -> Option<bool> {
+        self.source_content_at(source_id).map(str::is_ascii)
+    }
+
+    /// Returns whether the contents of all the sources are pure ASCII, see
+    /// [content_is_ascii](Self::content_is_ascii). Sources without content are skipped.
+    pub fn all_content_ascii(&self) -> bool {
+        self.sources_content
+            .iter()
+            .flatten()
+            .all(|content| content.is_ascii())
+    }
+
+    /// Converts a column in UTF-16 code units, as reported by browsers for JavaScript,
+    /// to a column in UTF-8 bytes on the given line of the source, using its content.
+    ///
+    /// Returns `None` if the source has no content, the line does not exist, or the column
+    /// is beyond the end of the line or in the middle of a character.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(
+    ///     r#"{"version":3,"sources":["a.js"],"sourcesContent":["let s = 'é'; s;"],"mappings":""}"#.into(),
+    /// ).unwrap();
+    /// // `é` takes 1 code unit in UTF-16, and 2 bytes in UTF-8
+    /// assert_eq!(sm.utf16_to_utf8_column(0, 0, 11), Some(12));
+    /// ```
+    pub fn utf16_to_utf8_column(&self, source_id: u32, line: u32, utf16_col: u32) -> Option<u32> {
+        let line = self.source_line_text(source_id, line)?;
+        let mut utf16 = 0;
+        for (utf8, char) in line.char_indices() {
+            if utf16 >= utf16_col {
+                return (utf16 == utf16_col).then_some(utf8 as u32);
+            }
+            utf16 += char.len_utf16() as u32;
+        }
+        (utf16 == utf16_col).then_some(line.len() as u32)
+    }
+
+    /// Converts a column in UTF-8 bytes to a column in UTF-16 code units on the given line of
+    /// the source, using its content. It's the inverse of
+    /// [utf16_to_utf8_column](Self::utf16_to_utf8_column).
+    ///
+    /// Returns `None` if the source has no content, the line does not exist, or the column
+    /// is beyond the end of the line or in the middle of a character.
+    pub fn utf8_to_utf16_column(&self, source_id: u32, line: u32, utf8_col: u32) -> Option<u32> {
+        let line = self.source_line_text(source_id, line)?;
+        let prefix = line.get(..utf8_col as usize)?;
+        Some(prefix.encode_utf16().count() as u32)
+    }
+
+    /// Finds the mapping for a byte offset into the generated code, like
+    /// [find_mapping](Self::find_mapping).
+    ///
+    /// The offset is converted to a position by scanning the newlines of `generated_code` up to
+    /// the offset, which is `O(offset)`. The column is counted in UTF-16 code units as specified
+    /// for source maps. Returns `None` if the offset is beyond the end of the code or in the
+    /// middle of a character.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;AACA,IAAI"}"#.to_vec()).unwrap();
+    /// let found = sm.find_mapping_at_offset("a;\nlet b;", 7).unwrap();
+    /// assert_eq!((found.generated().line, found.generated().column), (1, 4));
+    /// ```
+    pub fn find_mapping_at_offset(&self, generated_code: &str, offset: usize) -> Option<Mapping> {
+        let prefix = generated_code.get(..offset)?;
+        let line_start = memchr::memrchr(b'\n', prefix.as_bytes()).map_or(0, |idx| idx + 1);
+        let line = memchr::memchr_iter(b'\n', prefix.as_bytes()).count() as u32;
+        let column = prefix[line_start..].encode_utf16().count() as u32;
+        self.find_mapping(Position::new(line, column))
+    }
+
+    /// Returns the given line of the source content, without the line terminator.
+    ///
+    /// Lines are split on `\n`, with a trailing `\r` trimmed, so multi-byte characters are
+    /// never cut. Returns `None` if the source has no content or the line is beyond the end.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::SourceMap;
+    /// let sm = SourceMap::from(
+    ///     r#"{"version":3,"sources":["a.js"],"sourcesContent":["a;\r\nlet é;"],"mappings":""}"#.into(),
+    /// ).unwrap();
+    /// assert_eq!(sm.source_line_text(0, 0), Some("a;"));
+    /// assert_eq!(sm.source_line_text(0, 1), Some("let é;"));
+    /// assert_eq!(sm.source_line_text(0, 2), None);
+    /// ```
+    pub fn source_line_text(&self, source_id: u32, line: u32) -> Option<&str> {
+        let content = self.sources_content.get(source_id as usize)?.as_deref()?;
+        let line = content.split('\n').nth(line as usize)?;
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+}
@@ -1,7 +1,22 @@
+use crate::{ParseError, ParseResult};
 use memchr::Memchr2;
 
+/// `MappingSplitter` splits the VLQ encoded `mappings` into segments, without decoding them.
+///
+/// Each item is a segment along with whether it's followed by a `;`, i.e. the next segment is
+/// on the next generated line. Empty segments are yielded too, e.g. for empty lines.
+/// Stray `\r` bytes around the segments, e.g. from mangled line endings, are trimmed, while the
+/// ones inside are skipped when decoding.
+/// See [enumerate_lines](Self::enumerate_lines) to get the generated line of each segment.
+///
+/// # Example
+/// ```
+/// # use sora::MappingSplitter;
+/// let segments = MappingSplitter::new("AAAA,CAAC;;AACA").collect::<Vec<_>>();
+/// assert_eq!(segments, vec![("AAAA", false), ("CAAC", true), ("", true), ("AACA", false)]);
+/// ```
 #[derive(Debug)]
-pub(crate) struct MappingSplitter<'a> {
+pub struct MappingSplitter<'a> {
     string: &'a str,
     cur_start: usize,
     memchr: Memchr2<'a>,
@@ -15,6 +30,45 @@ impl<'a> MappingSplitter<'a> {
             cur_start: 0,
         }
     }
+
+    /// Yields each segment along with its 0-based generated line, so the consumers don't need
+    /// to count the `;`s themselves.
+    ///
+    /// Like the parsing, it yields [PositionOverflow](ParseError::PositionOverflow) and stops
+    /// if a `;` follows the line `u32::MAX`.
+    ///
+    /// # Example
+    /// ```
+    /// # use sora::MappingSplitter;
+    /// let segments = MappingSplitter::new("AAAA,CAAC;;AACA").enumerate_lines().collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(segments.unwrap(), vec![(0, "AAAA"), (0, "CAAC"), (1, ""), (2, "AACA")]);
+    /// ```
+    pub fn enumerate_lines(self) -> impl Iterator<Item = ParseResult<(u32, &'a str)>> {
+        self.enumerate_lines_from(0)
+    }
+
+    fn enumerate_lines_from(
+        mut self,
+        line: u32,
+    ) -> impl Iterator<Item = ParseResult<(u32, &'a str)>> {
+        // `None` once the line overflows, which is yielded as an error before stopping
+        let mut line = Some(line);
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let Some(current) = line else {
+                done = true;
+                return Some(Err(ParseError::PositionOverflow));
+            };
+            let (segment, new_line) = self.next()?;
+            if new_line {
+                line = current.checked_add(1);
+            }
+            Some(Ok((current, segment)))
+        })
+    }
 }
 
 impl<'a> Iterator for MappingSplitter<'a> {
@@ -24,6 +78,8 @@ impl<'a> Iterator for MappingSplitter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         let (cur_end, new_line) = match self.memchr.next() {
             None => {
+                // the segment after the last separator is always yielded, even if it is empty,
+                // and then `cur_start` goes past the end of the string
                 if self.cur_start > self.string.len() {
                     return None;
                 }
@@ -35,16 +91,19 @@ impl<'a> Iterator for MappingSplitter<'a> {
                 (end, ch == b';')
             }
         };
-        // SAFETY: cur_end never > self.string.len()
+        // SAFETY: cur_end never > self.string.len(), and both ends are next to an ASCII
+        // separator or at the ends of the string, so they are char boundaries
         let s = unsafe { self.string.get_unchecked(self.cur_start..cur_end) };
         self.cur_start = cur_end + 1;
-        Some((s, new_line))
+        Some((s.trim_matches('\r'), new_line))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::MappingSplitter;
+    use crate::mappings::MappingsDecoder;
+    use crate::{ParseError, ParseOptions};
 
     #[test]
     fn test_splitter() {
@@ -56,4 +115,82 @@ mod tests {
             .collect::<String>();
         insta::assert_snapshot!(result, @"[:true][:true][yZCTnK:false][IAAO5F:false][gBAAkB:false][YACzB:false][IAAOC:false][YAAcC:false][UACrB:true][IAAOC:false][oBAAsB:false][YAE7B:false][EAAQ:false][QAER:false][EAAQ:true][:true][cAAe:false]");
     }
+
+    #[test]
+    fn test_enumerate_lines() {
+        let text = ";;yZCTnK,IAAO5F;IAAOC,oBAAsB;;;cAAe,EAAQ;";
+        let options = ParseOptions {
+            insert_end_sentinel: false,
+            ..Default::default()
+        };
+        let mappings = MappingsDecoder::new(text)
+            .items_count(u32::MAX, u32::MAX)
+            .options(&options)
+            .decode()
+            .unwrap();
+
+        let lines = MappingSplitter::new(text)
+            .enumerate_lines()
+            .map(Result::unwrap)
+            .filter(|(_, segment)| !segment.is_empty())
+            .map(|(line, _)| line)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            mappings
+                .iter()
+                .map(|m| m.generated().line)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(lines, vec![2, 2, 3, 3, 6, 6]);
+
+        let last = MappingSplitter::new(text).enumerate_lines().last();
+        assert!(matches!(last, Some(Ok((7, "")))));
+    }
+
+    #[test]
+    fn test_enumerate_lines_overflow() {
+        let lines = MappingSplitter::new("A,B;C")
+            .enumerate_lines_from(u32::MAX)
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            lines[..],
+            [
+                Ok((u32::MAX, "A")),
+                Ok((u32::MAX, "B")),
+                Err(ParseError::PositionOverflow)
+            ]
+        ));
+        // the decoder fails the same way
+        #[cfg(feature = "index-map")]
+        assert!(matches!(
+            MappingsDecoder::new("A,B;C")
+                .state(u32::MAX, 0, 0, 0)
+                .decode(),
+            Err(ParseError::PositionOverflow)
+        ));
+
+        let lines = MappingSplitter::new("A,B")
+            .enumerate_lines_from(u32::MAX)
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            lines[..],
+            [Ok((u32::MAX, "A")), Ok((u32::MAX, "B"))]
+        ));
+    }
+
+    #[test]
+    fn test_splitter_trailing_separators() {
+        let split = |text| {
+            MappingSplitter::new(text)
+                .map(|(s, n)| format!("[{}:{}]", s, n))
+                .collect::<String>()
+        };
+
+        insta::assert_snapshot!(split("AAAA;"), @"[AAAA:true][:false]");
+        insta::assert_snapshot!(split("AAAA,"), @"[AAAA:false][:false]");
+        insta::assert_snapshot!(split(";;;"), @"[:true][:true][:true][:false]");
+        insta::assert_snapshot!(split(""), @"[:false]");
+        insta::assert_snapshot!(split("AAAA\r;\rAA\rAA,\r"), @"[AAAA:true][AA\rAA:false][:false]");
+    }
 }
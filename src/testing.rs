@@ -0,0 +1,105 @@
+//! Helpers for testing crates built on sora, e.g. checking the output against golden maps.
+//!
+//! ```
+//! # use sora::SourceMap;
+//! # use sora::testing::assert_mappings_eq;
+//! let a = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC"}"#.to_vec()).unwrap();
+//! let b = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC"}"#.to_vec()).unwrap();
+//! assert_mappings_eq(a.mappings(), b.mappings());
+//! ```
+
+use crate::Mappings;
+use std::fmt::Write;
+
+/// Asserts that two [Mappings] are equal.
+///
+/// On mismatch, the panic message shows the first divergent mapping of both sides along with
+/// the preceding one, which is easier to read than the full `Debug` output of the mappings.
+///
+/// # Panics
+///
+/// Panics if the mappings are not equal.
+#[track_caller]
+pub fn assert_mappings_eq(left: &Mappings, right: &Mappings) {
+    if let Some(diff) = mappings_diff(left, right) {
+        panic!("{}", diff);
+    }
+}
+
+/// Describes the first difference between the mappings, or returns `None` if they are equal.
+fn mappings_diff(left: &Mappings, right: &Mappings) -> Option<String> {
+    let idx = left
+        .iter()
+        .zip(right.iter())
+        .position(|(l, r)| l != r)
+        .or_else(|| (left.len() != right.len()).then(|| left.len().min(right.len())))?;
+
+    let mut diff = format!(
+        "mappings differ at index {} (left has {}, right has {} mappings)",
+        idx,
+        left.len(),
+        right.len()
+    );
+    if let Some(prev) = idx.checked_sub(1).and_then(|i| left.get(i)) {
+        let _ = write!(diff, "\n  previous: {:?}", prev);
+    }
+    let _ = write!(diff, "\n  left:     {}", describe(left, idx));
+    let _ = write!(diff, "\n  right:    {}", describe(right, idx));
+    Some(diff)
+}
+
+fn describe(mappings: &Mappings, idx: usize) -> String {
+    match mappings.get(idx) {
+        Some(mapping) => format!("{:?}", mapping),
+        None => "<none>".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_mappings_eq, mappings_diff};
+    use crate::mappings::MappingsDecoder;
+    use crate::Mappings;
+
+    fn decode(mappings: &str) -> Mappings {
+        MappingsDecoder::new(mappings)
+            .items_count(2, 1)
+            .decode()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_mappings_diff() {
+        let a = decode("AAAA,CAAC;AACAA");
+        assert_eq!(mappings_diff(&a, &decode("AAAA,CAAC;AACAA")), None);
+
+        assert_eq!(
+            mappings_diff(&a, &decode("AAAA,CAAE;AACAA")).unwrap(),
+            "mappings differ at index 1 (left has 3, right has 3 mappings)
+  previous: 0:0 -> 0:0:0
+  left:     0:1 -> 0:0:1
+  right:    0:1 -> 0:0:2"
+        );
+
+        assert_eq!(
+            mappings_diff(&a, &decode("AAAA,CAAC")).unwrap(),
+            "mappings differ at index 2 (left has 3, right has 2 mappings)
+  previous: 0:1 -> 0:0:1
+  left:     1:0 -> 0:1:1 (0)
+  right:    <none>"
+        );
+
+        assert_eq!(
+            mappings_diff(&Mappings::default(), &decode("AAAA")).unwrap(),
+            "mappings differ at index 0 (left has 0, right has 1 mappings)
+  left:     <none>
+  right:    0:0 -> 0:0:0"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "mappings differ at index 1")]
+    fn test_assert_mappings_eq() {
+        assert_mappings_eq(&decode("AAAA,CAAC"), &decode("AAAA,EAAC"));
+    }
+}
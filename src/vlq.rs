@@ -4,27 +4,89 @@ use std::io;
 use std::io::Write;
 
 const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-const BASE64_VALUES: [i8; 256] = get_base64_map();
+const URL_SAFE_BASE64_CHARS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
 
-const fn get_base64_map() -> [i8; 256] {
+const fn get_base64_map(chars: &[u8]) -> [i8; 256] {
     let mut res = [-1i8; 256];
     // `for in` is not allowed in const fn
     let mut idx = 0;
     while idx < 64 {
-        res[BASE64_CHARS[idx] as usize] = idx as i8;
+        res[chars[idx] as usize] = idx as i8;
         idx += 1;
     }
     res
 }
 
+/// `VlqAlphabet` is the base64 alphabet used to decode the VLQ encoded `mappings`.
+///
+/// The [standard](VlqAlphabet::STANDARD) alphabet is used by default. A few legacy tools emit
+/// the [URL-safe](VlqAlphabet::URL_SAFE) one, or another custom table, see
+/// [from_slice_with_alphabet](crate::BorrowedSourceMap::from_slice_with_alphabet).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VlqAlphabet {
+    values: [i8; 256],
+}
+
+impl VlqAlphabet {
+    /// The standard base64 alphabet, with `+` and `/`.
+    pub const STANDARD: Self = Self {
+        values: get_base64_map(BASE64_CHARS),
+    };
+
+    /// The URL-safe base64 alphabet, with `-` and `_`.
+    pub const URL_SAFE: Self = Self {
+        values: get_base64_map(URL_SAFE_BASE64_CHARS),
+    };
+
+    /// Creates an alphabet from a table of 64 distinct ASCII characters, where the character at
+    /// index `i` encodes the digit `i`.
+    ///
+    /// `,` and `;` are not allowed since they separate the segments of `mappings`, nor is `\r`
+    /// since stray ones are trimmed from the segments, see [MappingSplitter](crate::MappingSplitter).
+    pub fn new(chars: &str) -> ParseResult<Self> {
+        let chars = chars.as_bytes();
+        if unlikely!(chars.len() != 64) {
+            return Err(ParseError::InvalidAlphabet);
+        }
+        let mut values = [-1i8; 256];
+        for (idx, &char) in chars.iter().enumerate() {
+            if unlikely!(!char.is_ascii() || matches!(char, b',' | b';' | b'\r'))
+                || unlikely!(values[char as usize] != -1)
+            {
+                return Err(ParseError::InvalidAlphabet);
+            }
+            values[char as usize] = idx as i8;
+        }
+        Ok(Self { values })
+    }
+}
+
+impl Default for VlqAlphabet {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
 #[derive(Debug)]
-pub(crate) struct VlqDecoder {
+pub(crate) struct VlqDecoder<'a> {
     buf: [i64; 5],
+    values: &'a [i8; 256],
 }
 
-impl VlqDecoder {
+#[cfg(test)]
+impl VlqDecoder<'static> {
     pub fn new() -> Self {
-        Self { buf: [0; 5] }
+        VlqDecoder::with_alphabet(&VlqAlphabet::STANDARD)
+    }
+}
+
+impl<'a> VlqDecoder<'a> {
+    pub fn with_alphabet(alphabet: &'a VlqAlphabet) -> Self {
+        Self {
+            buf: [0; 5],
+            values: &alphabet.values,
+        }
     }
 
     pub fn decode(&mut self, segment: &str) -> ParseResult<&[i64]> {
@@ -34,7 +96,14 @@ impl VlqDecoder {
         let mut shift = 0;
 
         for byte in segment.bytes() {
-            let value = BASE64_VALUES[byte as usize] as i64;
+            let value = self.values[byte as usize] as i64;
+            if unlikely!(value < 0) {
+                // a stray `\r`, e.g. from a mangled line ending, is never a base64 digit
+                if byte == b'\r' {
+                    continue;
+                }
+                return Err(ParseError::MappingMalformed(segment.to_owned()));
+            }
             let val = value & 0b11111;
             cur_value += val
                 .checked_shl(shift)
@@ -108,9 +177,30 @@ where
     }
 }
 
+/// Encodes the bytes with the standard base64 alphabet, with padding.
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(4 * (bytes.len() / 3 + usize::from(bytes.len() % 3 != 0)));
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize;
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                out.push(BASE64_CHARS[(n >> (18 - idx * 6)) & 0b111111] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{VlqDecoder, VlqEncoder};
+    use super::{encode_base64, VlqAlphabet, VlqDecoder, VlqEncoder};
     use crate::ParseError;
 
     fn encode_helper(vlq: &[i64]) -> Vec<u8> {
@@ -122,6 +212,16 @@ mod tests {
         buf
     }
 
+    #[test]
+    fn test_encode_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foob"), "Zm9vYg==");
+        assert_eq!(encode_base64(&[0xff, 0xfe]), "//4=");
+    }
+
     #[test]
     fn test_vlq_decode_encode_normal() {
         let mut decoder = VlqDecoder::new();
@@ -147,4 +247,51 @@ mod tests {
             Err(ParseError::MappingMalformed(..))
         ));
     }
+
+    #[test]
+    fn test_vlq_decode_carriage_return() {
+        let mut decoder = VlqDecoder::new();
+        assert_eq!(decoder.decode("AA\rCA").unwrap(), &[0, 0, 1, 0]);
+        assert_eq!(decoder.decode("\rAAAA\r").unwrap(), &[0, 0, 0, 0]);
+        assert_eq!(decoder.decode("\r").unwrap(), &[] as &[i64]);
+        // other invalid characters are not skipped
+        assert!(matches!(
+            decoder.decode("A=A"),
+            Err(ParseError::MappingMalformed(..))
+        ));
+        assert!(matches!(
+            decoder.decode("AA\nAA"),
+            Err(ParseError::MappingMalformed(..))
+        ));
+    }
+
+    #[test]
+    fn test_vlq_decode_url_safe() {
+        let mut decoder = VlqDecoder::with_alphabet(&VlqAlphabet::URL_SAFE);
+        // "-" and "_" replace "+" and "/" of the standard alphabet
+        assert_eq!(decoder.decode("-_B").unwrap(), &[1023]);
+        assert_eq!(VlqDecoder::new().decode("+/B").unwrap(), &[1023]);
+        assert_eq!(decoder.decode("AAAA").unwrap(), &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_vlq_alphabet_validation() {
+        let standard = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        assert_eq!(VlqAlphabet::new(standard).unwrap(), VlqAlphabet::STANDARD);
+        assert_eq!(VlqAlphabet::default(), VlqAlphabet::STANDARD);
+
+        let url_safe = standard.replace('+', "-").replace('/', "_");
+        assert_eq!(VlqAlphabet::new(&url_safe).unwrap(), VlqAlphabet::URL_SAFE);
+
+        // too short, too long
+        assert!(VlqAlphabet::new(&standard[1..]).is_err());
+        assert!(VlqAlphabet::new(&format!("{}=", standard)).is_err());
+        // duplicated
+        assert!(VlqAlphabet::new(&standard.replace('/', "+")).is_err());
+        // separators and non-ASCII
+        assert!(VlqAlphabet::new(&standard.replace('/', ",")).is_err());
+        assert!(VlqAlphabet::new(&standard.replace('/', ";")).is_err());
+        assert!(VlqAlphabet::new(&standard.replace('/', "\r")).is_err());
+        assert!(VlqAlphabet::new(&standard.replace("+/", "é")).is_err());
+    }
 }
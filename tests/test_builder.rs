@@ -1,6 +1,6 @@
 #![cfg(feature = "builder")]
 
-use sora::{Mapping, Mappings, SourceMap, ValidateError};
+use sora::{Mapping, Mappings, ParseError, SourceMap, ValidateError};
 use std::borrow::Cow;
 
 #[test]
@@ -26,3 +26,141 @@ fn test_sourcemap_builder() {
         Err(ValidateError::MismatchSourcesContent { .. })
     ))
 }
+
+#[test]
+fn test_sourcemap_builder_with_encoded_mappings() {
+    let sm = SourceMap::builder()
+        .with_sources(vec![
+            Some(Cow::Borrowed("a.js")),
+            Some(Cow::Borrowed("b.js")),
+        ])
+        .with_sources_content(vec![None, None])
+        .with_names(vec![Cow::Borrowed("foo")])
+        .with_encoded_mappings("AAAAA,ECAA;ADCA")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(
+        sm.mappings().to_vec(),
+        vec![
+            Mapping::new(0, 0).with_source(0, 0, 0).with_name(0),
+            Mapping::new(0, 2).with_source(1, 0, 0),
+            Mapping::new(1, 0).with_source(0, 1, 0),
+        ]
+    );
+    insta::assert_snapshot!(sm.to_string().unwrap(), @r###"{"version":3,"sources":["a.js","b.js"],"sourcesContent":[null,null],"names":["foo"],"mappings":"AAAAA,ECAA;ADCA"}"###);
+
+    // sources and names must be set before
+    let err = SourceMap::builder()
+        .with_encoded_mappings("AAAA")
+        .unwrap_err();
+    assert!(matches!(err, ParseError::UnknownSourceReference(0)));
+    let err = SourceMap::builder()
+        .with_sources(vec![None])
+        .with_encoded_mappings("AAAAA")
+        .unwrap_err();
+    assert!(matches!(err, ParseError::UnknownNameReference(0)));
+}
+
+#[test]
+fn test_mappings_from_iter() {
+    // a generator emitting mappings out of order
+    let mut mappings = (0..4u32)
+        .rev()
+        .map(|i| Mapping::new(i / 2, i % 2 * 4).with_source(0, i, 0))
+        .collect::<Mappings>();
+    assert_eq!(
+        mappings.to_vec(),
+        vec![
+            Mapping::new(0, 0).with_source(0, 0, 0),
+            Mapping::new(0, 4).with_source(0, 1, 0),
+            Mapping::new(1, 0).with_source(0, 2, 0),
+            Mapping::new(1, 4).with_source(0, 3, 0),
+        ]
+    );
+    assert_eq!(
+        mappings.find_mapping((1, 2)),
+        Some(Mapping::new(1, 0).with_source(0, 2, 0))
+    );
+
+    mappings.extend([Mapping::new(2, 0), Mapping::new(0, 2)]);
+    assert_eq!(mappings.len(), 6);
+    assert!(mappings.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(mappings.find_mapping((0, 3)), Some(Mapping::new(0, 2)));
+    assert_eq!(mappings.find_mapping((5, 0)), Some(Mapping::new(2, 0)));
+}
+
+#[test]
+fn test_mappings_clear() {
+    let mut mappings = Mappings::with_capacity(16);
+    assert!(mappings.is_empty());
+    assert!(mappings.capacity() >= 16);
+
+    for round in 0..3 {
+        mappings.extend((0..16).map(|i| Mapping::new(round, i)));
+        assert_eq!(mappings.len(), 16);
+        let capacity = mappings.capacity();
+
+        mappings.clear();
+        assert!(mappings.is_empty());
+        assert_eq!(mappings.find_mapping((round, 0)), None);
+        assert_eq!(mappings.capacity(), capacity);
+    }
+}
+
+#[test]
+fn test_sourcemap_builder_applied_source_root() {
+    let builder = || {
+        SourceMap::builder()
+            .with_source_root(Cow::Borrowed("https://example.com/src//"))
+            .with_sources(vec![
+                Some(Cow::Borrowed("a.js")),
+                Some(Cow::Borrowed("/abs/b.js")),
+                Some(Cow::Borrowed("http://other.com/c.js")),
+                None,
+            ])
+            .with_sources_content(vec![None; 4])
+    };
+
+    let sm = builder().with_applied_source_root(true).build().unwrap();
+    assert_eq!(sm.source_root(), &None);
+    assert_eq!(
+        sm.sources(),
+        &[
+            Some(Cow::Borrowed("https://example.com/src/a.js")),
+            Some(Cow::Borrowed("/abs/b.js")),
+            Some(Cow::Borrowed("http://other.com/c.js")),
+            None,
+        ]
+    );
+
+    // opt-in only
+    let sm = builder().build().unwrap();
+    assert_eq!(
+        sm.source_root().as_deref(),
+        Some("https://example.com/src//")
+    );
+    assert_eq!(sm.sources()[0].as_deref(), Some("a.js"));
+}
+
+#[test]
+fn test_sourcemap_builder_push_source_full() {
+    let mut builder = SourceMap::builder();
+    assert_eq!(
+        builder.push_source_full(Some(Cow::Borrowed("a.js")), Some(Cow::Borrowed("a"))),
+        0
+    );
+    assert_eq!(builder.push_source_full(None, None), 1);
+    let sm = builder.build().unwrap();
+    assert_eq!(sm.sources(), &[Some(Cow::Borrowed("a.js")), None]);
+    assert_eq!(sm.sources_content(), &[Some(Cow::Borrowed("a")), None]);
+
+    // the content is padded for the sources set without it
+    let mut builder = SourceMap::builder().with_sources(vec![Some(Cow::Borrowed("a.js"))]);
+    assert_eq!(
+        builder.push_source_full(Some(Cow::Borrowed("b.js")), Some(Cow::Borrowed("b"))),
+        1
+    );
+    let sm = builder.build().unwrap();
+    assert_eq!(sm.sources_content(), &[None, Some(Cow::Borrowed("b"))]);
+}
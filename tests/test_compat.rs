@@ -0,0 +1,69 @@
+#![cfg(feature = "compat-sourcemap")]
+
+use sora::SourceMap;
+
+#[test]
+fn test_lookup_token() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"names":["foo"],"mappings":"AAAA,EAACA;ACCA,G"}"#
+            .to_vec(),
+    )
+    .unwrap();
+
+    let token = sm.lookup_token(0, 3).unwrap();
+    assert_eq!(token.get_dst(), (0, 2));
+    assert_eq!(
+        (
+            token.get_src_id(),
+            token.get_src_line(),
+            token.get_src_col()
+        ),
+        (0, 0, 1)
+    );
+    assert_eq!(token.get_source(), Some("a.js"));
+    assert_eq!(token.get_name(), Some("foo"));
+    assert_eq!(token.to_string(), "a.js:0:1");
+
+    let token = sm.lookup_token(1, 0).unwrap();
+    assert_eq!(
+        (
+            token.get_src_id(),
+            token.get_src_line(),
+            token.get_src_col()
+        ),
+        (1, 1, 1)
+    );
+    assert_eq!(token.get_source(), Some("b.js"));
+    assert!(!token.has_name());
+    assert_eq!(token.get_name_id(), !0);
+
+    // a token without source information
+    let token = sm.lookup_token(1, 10).unwrap();
+    assert_eq!(token.get_dst(), (1, 3));
+    assert!(!token.has_source());
+    assert_eq!(
+        (
+            token.get_src_id(),
+            token.get_src_line(),
+            token.get_src_col()
+        ),
+        (!0, !0, !0)
+    );
+    assert_eq!(token.get_source(), None);
+
+    // tokens agree with find_mapping
+    for line in 0..3 {
+        for col in 0..5 {
+            assert_eq!(
+                sm.lookup_token(line, col)
+                    .map(|token| token.mapping().clone()),
+                sm.find_mapping((line, col))
+            );
+        }
+    }
+
+    assert_eq!(sm.get_token_count(), 4);
+    assert_eq!(sm.tokens().count(), 4);
+    assert_eq!(sm.get_token(3).unwrap().get_dst(), (1, 3));
+    assert!(sm.get_token(4).is_none());
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "ignore_list")]
+
+use sora::{
+    BorrowedSourceMap, CompatMode, LoadError, ParseError, ParseWarning, SourceMap, ValidateError,
+    WriteOptions,
+};
+
+#[test]
+fn test_validate_ignore_list() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["a",null],"mappings":"","ignoreList":[0]}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(sm.validate_ignore_list().is_ok());
+
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["a",null],"mappings":"","ignoreList":[0,1]}"#
+            .to_vec(),
+    )
+    .unwrap();
+    // the default validation doesn't check the ignore list
+    assert!(sm.validate().is_ok());
+    assert!(matches!(
+        sm.validate_ignore_list(),
+        Err(ValidateError::IgnoredSourceWithoutContent(1))
+    ));
+
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"sourcesContent":["a"],"mappings":"","ignoreList":[2]}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(matches!(
+        sm.validate_ignore_list(),
+        Err(ValidateError::UnknownSourceReference(2))
+    ));
+}
+
+#[test]
+fn test_warn_ignore_list_out_of_range() {
+    let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"","ignoreList":[0,1]}"#.to_vec();
+    let (sm, warnings) = BorrowedSourceMap::from_slice_with_warnings(&mut buf).unwrap();
+    assert_eq!(sm.ignore_list(), &[0, 1]);
+    assert_eq!(warnings, vec![ParseWarning::IgnoreListOutOfRange(1)]);
+
+    let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"","ignoreList":[0]}"#.to_vec();
+    let (_, warnings) = BorrowedSourceMap::from_slice_with_warnings(&mut buf).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_from_slice_validated() {
+    let mut buf =
+        br#"{"version":3,"sources":["a.js"],"sourcesContent":["a"],"mappings":"AAAA","ignoreList":[0]}"#
+            .to_vec();
+    assert!(BorrowedSourceMap::from_slice_validated(&mut buf).is_ok());
+
+    // parses, but fails the validation
+    let mut buf =
+        br#"{"version":3,"sources":["a.js"],"mappings":"AAAA","ignoreList":[0]}"#.to_vec();
+    assert!(BorrowedSourceMap::from_slice(&mut buf.clone()).is_ok());
+    let err = BorrowedSourceMap::from_slice_validated(&mut buf).unwrap_err();
+    assert!(matches!(
+        err,
+        LoadError::Validate(ValidateError::IgnoredSourceWithoutContent(0))
+    ));
+    assert_eq!(
+        err.to_string(),
+        "source map is invalid: ignore list references source #0 which has no content"
+    );
+
+    let mut buf = br#"{"version":2,"mappings":""}"#.to_vec();
+    assert!(matches!(
+        BorrowedSourceMap::from_slice_validated(&mut buf),
+        Err(LoadError::Parse(ParseError::UnsupportedFormat))
+    ));
+}
+
+#[test]
+#[cfg(feature = "builder")]
+fn test_builder_with_ignore_list() {
+    let sm = SourceMap::builder()
+        .with_sources(vec![Some("a.js".into()), Some("vendor.js".into())])
+        .with_sources_content(vec![None, None])
+        .with_encoded_mappings("AAAA,ECAA")
+        .unwrap()
+        .with_ignore_list(vec![1])
+        .build()
+        .unwrap();
+    assert_eq!(sm.ignore_list(), &[1]);
+    assert_eq!(
+        sm.to_string().unwrap(),
+        r#"{"version":3,"sources":["a.js","vendor.js"],"sourcesContent":[null,null],"mappings":"AAAA,ECAA","ignoreList":[1]}"#
+    );
+
+    let err = SourceMap::builder()
+        .with_sources(vec![Some("a.js".into())])
+        .with_sources_content(vec![None])
+        .with_ignore_list(vec![0, 1])
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, ValidateError::UnknownSourceReference(1)));
+}
+
+#[test]
+fn test_compat_mode_ignore_list() {
+    let json = br#"{"version":3,"sources":["a.js","b.js"],"mappings":"","ignoreList":[1,5]}"#;
+    let sm = BorrowedSourceMap::from_slice_with_mode(&mut json.to_vec(), CompatMode::Strict)
+        .unwrap()
+        .into_owned();
+    assert_eq!(sm.ignore_list(), &[1, 5]);
+    let sm = BorrowedSourceMap::from_slice_with_mode(&mut json.to_vec(), CompatMode::Chrome)
+        .unwrap()
+        .into_owned();
+    assert_eq!(sm.ignore_list(), &[1]);
+}
+
+#[test]
+fn test_legacy_ignore_list() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"","x_google_ignoreList":[1]}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.ignore_list(), &[1]);
+
+    // the legacy entries are merged into the standard ones
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js","c.js"],"mappings":"","ignoreList":[1],"x_google_ignoreList":[2,1]}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.ignore_list(), &[1, 2]);
+
+    let mut options = WriteOptions::default();
+    options.legacy_ignore_list = true;
+    let mut buf = vec![];
+    sm.write_with_options(&mut buf, &options).unwrap();
+    let json = String::from_utf8(buf).unwrap();
+    assert!(json.contains(r#""ignoreList":[1,2],"x_google_ignoreList":[1,2]"#));
+    assert!(!sm.to_string().unwrap().contains("x_google_ignoreList"));
+}
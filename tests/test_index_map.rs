@@ -0,0 +1,251 @@
+#![cfg(feature = "index-map")]
+
+use sora::{BorrowedSourceMap, CompatMode, ParseError, ParseOptions, ParseWarning, SourceMap};
+
+#[test]
+fn test_index_map_source_root() {
+    let sm = SourceMap::from(
+        br#"{
+            "version": 3,
+            "sourceRoot": "https://example.com/root/",
+            "sections": [
+                {
+                    "offset": { "line": 0, "column": 0 },
+                    "map": { "version": 3, "sources": ["a.js", "/abs/b.js"], "mappings": "AAAA" }
+                },
+                {
+                    "offset": { "line": 1, "column": 0 },
+                    "map": { "version": 3, "sourceRoot": "lib", "sources": ["c.js", null], "mappings": "AAAA" }
+                }
+            ]
+        }"#
+        .to_vec(),
+    )
+    .unwrap();
+
+    assert_eq!(sm.source_at(0), Some("https://example.com/root/a.js"));
+    assert_eq!(sm.source_at(1), Some("/abs/b.js"));
+    assert_eq!(sm.source_at(2), Some("https://example.com/root/lib/c.js"));
+    assert_eq!(sm.source_at(3), None);
+    // the source root has been applied to the sources
+    assert_eq!(sm.source_root(), &None);
+}
+
+#[test]
+fn test_index_map_position_overflow() {
+    let parse = |offset_line: u32, offset_column: u32, mappings: &str| {
+        SourceMap::from(
+            format!(
+                r#"{{"version":3,"sections":[{{"offset":{{"line":{offset_line},"column":{offset_column}}},"map":{{"version":3,"mappings":"{mappings}"}}}}]}}"#
+            )
+            .into_bytes(),
+        )
+    };
+
+    assert!(parse(u32::MAX, 0, "A").is_ok());
+    assert!(matches!(
+        parse(u32::MAX, 0, "A;A"),
+        Err(ParseError::PositionOverflow)
+    ));
+    assert!(parse(0, u32::MAX - 1, "C").is_ok());
+    assert!(matches!(
+        parse(0, u32::MAX - 1, "E"),
+        Err(ParseError::PositionOverflow)
+    ));
+}
+
+#[test]
+#[cfg(feature = "ignore_list")]
+fn test_index_map_ignore_list_overflow() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sections":[
+            {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"mappings":"AAAA"}},
+            {"offset":{"line":1,"column":0},"map":{"version":3,"sources":["b.js"],"mappings":"AAAA","ignoreList":[4294967295,0]}}
+        ]}"#
+        .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.ignore_list(), &[1]);
+}
+
+#[test]
+fn test_index_map_lookup_table_huge_offset() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sections":[
+            {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"mappings":"AAAA"}},
+            {"offset":{"line":4294967290,"column":0},"map":{"version":3,"sources":["b.js"],"mappings":"AAAA,CAAC"}}
+        ]}"#
+        .to_vec(),
+    )
+    .unwrap();
+    // only the mapped lines are stored
+    let table = sm.lookup_table();
+    for (line, column) in [
+        (0, 0),
+        (1, 0),
+        (4294967290, 0),
+        (4294967290, 5),
+        (u32::MAX, 0),
+    ] {
+        assert_eq!(table.lookup(line, column), sm.find_mapping((line, column)));
+    }
+}
+
+fn parse_section(map: &str, options: &ParseOptions) -> sora::ParseResult<Vec<ParseWarning>> {
+    let mut buf = format!(
+        r#"{{"version":3,"sections":[
+            {{"offset":{{"line":0,"column":0}},"map":{{"version":3,"sources":["a.js"],"mappings":"AAAA"}}}},
+            {{"offset":{{"line":1,"column":0}},"map":{map}}}
+        ]}}"#
+    )
+    .into_bytes();
+    BorrowedSourceMap::from_slice_with_options_and_warnings(&mut buf, options).map(|(_, w)| w)
+}
+
+#[test]
+fn test_index_map_section_resize_sources_content() {
+    let map = r#"{"version":3,"sources":["b.js","c.js"],"sourcesContent":["b"],"mappings":"AAAA"}"#;
+    assert!(matches!(
+        parse_section(map, &ParseOptions::default()),
+        Err(ParseError::MismatchSourcesContent {
+            sources_len: 2,
+            sources_content_len: 1
+        })
+    ));
+
+    let mut buf = format!(
+        r#"{{"version":3,"sections":[{{"offset":{{"line":0,"column":0}},"map":{map}}},
+            {{"offset":{{"line":1,"column":0}},"map":{{"version":3,"sources":["d.js"],"sourcesContent":["d","e"],"mappings":"AAAA"}}}}]}}"#
+    )
+    .into_bytes();
+    let (sm, warnings) = BorrowedSourceMap::from_slice_with_options_and_warnings(
+        &mut buf,
+        &CompatMode::Chrome.into(),
+    )
+    .unwrap();
+    assert_eq!(
+        warnings,
+        vec![
+            ParseWarning::MismatchSourcesContent {
+                sources_len: 2,
+                sources_content_len: 1
+            },
+            ParseWarning::MismatchSourcesContent {
+                sources_len: 1,
+                sources_content_len: 2
+            },
+        ]
+    );
+    assert_eq!(sm.source_content_at(0), Some("b"));
+    assert_eq!(sm.source_content_at(1), None);
+    assert_eq!(sm.source_content_at(2), Some("d"));
+    assert_eq!(sm.sources().len(), 3);
+}
+
+#[test]
+fn test_index_map_section_version() {
+    // only the version of the index map is checked
+    for map in [
+        r#"{"sources":["b.js"],"mappings":"AAAA"}"#,
+        r#"{"version":2,"sources":["b.js"],"mappings":"AAAA"}"#,
+    ] {
+        assert!(parse_section(map, &ParseOptions::default()).is_ok());
+    }
+
+    let mut buf = br#"{"sections":[]}"#.to_vec();
+    assert!(matches!(
+        BorrowedSourceMap::from_slice(&mut buf),
+        Err(ParseError::UnsupportedFormat)
+    ));
+    let mut options = ParseOptions::default();
+    options.accept_missing_version = true;
+    let mut buf = br#"{"sections":[]}"#.to_vec();
+    assert!(BorrowedSourceMap::from_slice_with_options(&mut buf, &options).is_ok());
+}
+
+#[test]
+#[cfg(feature = "ignore_list")]
+fn test_index_map_section_drop_invalid_ignore_list() {
+    // the rebased entry of the first section would alias `b.js` of the second one
+    let json = br#"{"version":3,"sections":[
+        {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"mappings":"AAAA","ignoreList":[0,1]}},
+        {"offset":{"line":1,"column":0},"map":{"version":3,"sources":["b.js"],"mappings":"AAAA"}}
+    ]}"#;
+    for options in [ParseOptions::default(), CompatMode::Chrome.into()] {
+        let mut buf = json.to_vec();
+        let (sm, warnings) =
+            BorrowedSourceMap::from_slice_with_options_and_warnings(&mut buf, &options).unwrap();
+        assert_eq!(warnings, vec![ParseWarning::IgnoreListOutOfRange(1)]);
+        assert_eq!(sm.ignore_list(), &[0]);
+    }
+}
+
+#[test]
+fn test_index_map_generated_line_count_last_line() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sections":[
+            {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"mappings":"AAAA"}},
+            {"offset":{"line":4294967295,"column":0},"map":{"version":3,"sources":["b.js"],"mappings":"AAAA"}}
+        ]}"#
+        .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.generated_line_count(), u32::MAX);
+    assert_eq!(sm.mapped_coverage(), (2.0 / (u32::MAX as f64 + 1.0)) as f32);
+}
+
+#[test]
+fn test_index_map_filtered_section_order() {
+    // the discarded mappings still bound the next section
+    let mut buf = br#"{"version":3,"sections":[
+        {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"mappings":"AAAA,KAAK"}},
+        {"offset":{"line":0,"column":3},"map":{"version":3,"sources":["b.js"],"mappings":"AAAA"}}
+    ]}"#
+    .to_vec();
+    assert!(matches!(
+        BorrowedSourceMap::from_slice_filtered(&mut buf, |pos| pos.column != 5),
+        Err(ParseError::MappingsUnordered)
+    ));
+}
+
+#[test]
+#[cfg(feature = "ignore_list")]
+fn test_index_map_ignore_list_out_of_range_warning() {
+    let mut buf = br#"{"version":3,"sections":[
+        {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"mappings":"AAAA"}},
+        {"offset":{"line":1,"column":0},"map":{"version":3,"sources":["b.js"],"mappings":"AAAA","ignoreList":[0,1,4294967295]}}
+    ]}"#
+    .to_vec();
+    let (sm, warnings) =
+        BorrowedSourceMap::from_slice_with_options_and_warnings(&mut buf, &ParseOptions::default())
+            .unwrap();
+    // the ids of the flattened map
+    assert_eq!(
+        warnings,
+        vec![
+            ParseWarning::IgnoreListOutOfRange(2),
+            ParseWarning::IgnoreListOutOfRange(u32::MAX)
+        ]
+    );
+    assert_eq!(sm.ignore_list(), &[1]);
+}
+
+#[test]
+fn test_index_map_max_mappings() {
+    let parse = |max_mappings| {
+        let mut options = ParseOptions::default();
+        options.max_mappings = Some(max_mappings);
+        let mut buf = br#"{"version":3,"sections":[
+            {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"mappings":"AAAA;"}},
+            {"offset":{"line":2,"column":0},"map":{"version":3,"sources":["b.js"],"mappings":"AAAA;"}}
+        ]}"#
+        .to_vec();
+        BorrowedSourceMap::from_slice_with_options(&mut buf, &options).map(|sm| sm.mappings().len())
+    };
+    // the end sentinels of the sections don't count
+    assert_eq!(parse(2).unwrap(), 4);
+    assert!(matches!(
+        parse(1),
+        Err(ParseError::TooManyMappings { limit: 1 })
+    ));
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "lazy-content")]
+
+use sora::{BorrowedSourceMap, LazySourceMap, ParseError};
+
+#[test]
+fn test_lazy_content() {
+    let json = r#"{
+        "version": 3,
+        "sourcesContent": ["let s = \"\u00e9\";\n", null, "// \ud83e\udd80\r\n\\\\", "plain"],
+        "sources": ["a.js", "b.js", "c.js", "d.js"],
+        "names": ["s"],
+        "mappings": "AAAAA,CCAA,CCAA;ACAA"
+    }"#;
+    let expected = BorrowedSourceMap::from_slice(&mut json.as_bytes().to_vec())
+        .unwrap()
+        .into_owned();
+
+    let mut buf = json.as_bytes().to_vec();
+    let sm = LazySourceMap::from_slice(&mut buf).unwrap();
+    assert_eq!(sm.sources(), expected.sources());
+    assert_eq!(sm.names(), expected.names());
+    assert_eq!(sm.mappings(), expected.mappings());
+
+    for id in 0..5 {
+        assert_eq!(
+            sm.source_content(id).unwrap(),
+            expected.source_content_at(id)
+        );
+    }
+    assert_eq!(sm.source_content(0).unwrap(), Some("let s = \"é\";\n"));
+    assert_eq!(sm.source_content(2).unwrap(), Some("// 🦀\r\n\\\\"));
+
+    assert_eq!(sm.into_source_map().unwrap(), expected);
+}
+
+#[test]
+fn test_lazy_content_fixture() {
+    let json = include_bytes!("../benches/data/tiny.js.map").to_vec();
+    let expected = BorrowedSourceMap::from_slice(&mut json.clone())
+        .unwrap()
+        .into_owned();
+    assert!(expected.sources_content().iter().any(Option::is_some));
+
+    let mut buf = json.clone();
+    let sm = LazySourceMap::from_slice(&mut buf).unwrap();
+    for id in 0..expected.sources().len() as u32 {
+        assert_eq!(
+            sm.source_content(id).unwrap(),
+            expected.source_content_at(id)
+        );
+    }
+    let mut buf = json;
+    let sm = LazySourceMap::from_slice(&mut buf).unwrap();
+    assert_eq!(sm.into_source_map().unwrap(), expected);
+}
+
+#[test]
+fn test_lazy_content_fallback() {
+    // nothing to defer
+    for json in [
+        r#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#,
+        r#"{"version":3,"sources":["a.js"],"sourcesContent":null,"mappings":"AAAA"}"#,
+        r#"{"version":3,"sources":["a.js"],"sourcesContent":[],"mappings":"AAAA"}"#,
+    ] {
+        let mut buf = json.as_bytes().to_vec();
+        let sm = LazySourceMap::from_slice(&mut buf);
+        if json.contains("[]") {
+            assert!(matches!(
+                sm,
+                Err(ParseError::MismatchSourcesContent {
+                    sources_len: 1,
+                    sources_content_len: 0
+                })
+            ));
+        } else {
+            assert_eq!(sm.unwrap().source_content(0).unwrap(), None);
+        }
+    }
+
+    // the parser reports the errors
+    let mut buf =
+        br#"{"version":3,"sources":["a.js"],"sourcesContent":[1],"mappings":""}"#.to_vec();
+    assert!(matches!(
+        LazySourceMap::from_slice(&mut buf),
+        Err(ParseError::Syntax(_))
+    ));
+    let mut buf =
+        br#"{"version":3,"sources":["a.js"],"sourcesContent":["a","b"],"mappings":""}"#.to_vec();
+    assert!(matches!(
+        LazySourceMap::from_slice(&mut buf),
+        Err(ParseError::MismatchSourcesContent {
+            sources_len: 1,
+            sources_content_len: 2
+        })
+    ));
+}
+
+#[test]
+fn test_lazy_content_invalid() {
+    let mut buf =
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["\x","b"],"mappings":""}"#
+            .to_vec();
+    let sm = LazySourceMap::from_slice(&mut buf).unwrap();
+    // only the accessed content is checked
+    assert_eq!(sm.source_content(1).unwrap(), Some("b"));
+    assert!(matches!(
+        sm.source_content(0),
+        Err(ParseError::InvalidSourceContent(0))
+    ));
+    assert!(matches!(
+        sm.into_source_map(),
+        Err(ParseError::InvalidSourceContent(0))
+    ));
+}
+
+#[test]
+fn test_lazy_content_round_trip() {
+    let json = include_bytes!("../benches/data/tiny.js.map").to_vec();
+    let expected = BorrowedSourceMap::from_slice(&mut json.clone())
+        .unwrap()
+        .to_string()
+        .unwrap();
+
+    let mut buf = json;
+    let sm = LazySourceMap::from_slice(&mut buf).unwrap();
+    // the contents accessed before the conversion are kept as well
+    sm.source_content(0).unwrap();
+    let written = sm.into_source_map().unwrap().to_string().unwrap();
+    assert_eq!(written, expected);
+    assert!(written.contains(r#""sourcesContent":[""#));
+}
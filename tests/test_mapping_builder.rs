@@ -0,0 +1,14 @@
+use sora::Mapping;
+
+#[test]
+fn test_mapping_builder() {
+    assert_eq!(Mapping::builder(1, 2).build(), Mapping::new(1, 2));
+    assert_eq!(
+        Mapping::builder(1, 2).source(0, 3, 4).build(),
+        Mapping::new(1, 2).with_source(0, 3, 4)
+    );
+    assert_eq!(
+        Mapping::from(Mapping::builder(1, 2).source(0, 3, 4).name(5)),
+        Mapping::new(1, 2).with_source(0, 3, 4).with_name(5)
+    );
+}
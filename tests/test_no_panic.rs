@@ -0,0 +1,122 @@
+//! Feeds arbitrary bytes into the parsers, which must return `Err` instead of panicking.
+
+use sora::{BorrowedSourceMap, CompatMode, Mappings, Position, SourceMap};
+
+const TINY: &[u8] = include_bytes!("../benches/data/tiny.js.map");
+const INDEX_MAP: &[u8] = br#"{"version":3,"sourceRoot":"/","sections":[
+    {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"sourcesContent":["a"],"names":["x"],"mappings":"AAAAA,CAAC","ignoreList":[0]}},
+    {"offset":{"line":2,"column":4},"map":{"version":3,"sourceRoot":"b","sources":["b.js"],"mappings":"AAAA;AAAC"}},
+    {"offset":{"line":9,"column":0}}
+]}"#;
+
+/// A xorshift generator, to keep the inputs reproducible without extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize, alphabet: &[u8]) -> Vec<u8> {
+        (0..len)
+            .map(|_| alphabet[self.below(alphabet.len())])
+            .collect()
+    }
+}
+
+fn exercise(json: &[u8]) {
+    for mode in [CompatMode::Strict, CompatMode::Chrome, CompatMode::Node] {
+        let mut buf = json.to_vec();
+        let Ok(sm) = BorrowedSourceMap::from_slice_with_mode(&mut buf, mode) else {
+            continue;
+        };
+        let _ = sm.validate();
+        // the encoded `mappings` has a `;` per line, skip writing gigabytes for huge offsets
+        if sm.mappings().last().map_or(0, |m| m.generated().line) < 1 << 16 {
+            let _ = sm.to_string();
+        }
+        let table = sm.lookup_table();
+        let _ = table.lookup(u32::MAX, u32::MAX);
+        let finder = sm.finder();
+        for mapping in sm.mappings().iter() {
+            let pos = mapping.generated();
+            let _ = finder.find_mapping(pos);
+            let _ = finder.find_mapping(Position::new(pos.line, pos.column.saturating_add(1)));
+        }
+        let _ = finder.find_mapping(Position::max());
+    }
+    let _ = BorrowedSourceMap::from_slice_validated(&mut json.to_vec());
+    let _ = SourceMap::from(json.to_vec());
+    let _ = Mappings::from_bytes(json);
+}
+
+/// Replaces, inserts or removes random bytes of the `json`.
+fn mutate(rng: &mut Rng, json: &[u8]) -> Vec<u8> {
+    let mut json = json.to_vec();
+    for _ in 0..=rng.below(8) {
+        let idx = rng.below(json.len() + 1);
+        match rng.below(3) {
+            0 if idx < json.len() => json[idx] = rng.next() as u8,
+            1 => json.insert(idx, rng.next() as u8),
+            _ if idx < json.len() => {
+                json.remove(idx);
+            }
+            _ => {}
+        }
+    }
+    json
+}
+
+#[test]
+fn test_random_bytes() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..2000 {
+        let len = rng.below(64);
+        exercise(&rng.bytes(len, &(0..=255).collect::<Vec<_>>()));
+    }
+}
+
+#[test]
+fn test_mutated_maps() {
+    assert!(SourceMap::from(TINY.to_vec()).is_ok());
+    #[cfg(feature = "index-map")]
+    assert!(SourceMap::from(INDEX_MAP.to_vec()).is_ok());
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for fixture in [TINY, INDEX_MAP] {
+        for _ in 0..2000 {
+            exercise(&mutate(&mut rng, fixture));
+        }
+    }
+}
+
+#[test]
+fn test_random_mappings() {
+    const VLQ: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/-_,;;\r";
+    let mut rng = Rng(0xdead_beef_cafe_f00d);
+    for _ in 0..2000 {
+        let len = rng.below(48);
+        let mappings = String::from_utf8(rng.bytes(len, VLQ)).unwrap();
+        let ignore_list = rng.next() as u32;
+        exercise(
+            format!(
+                r#"{{"version":3,"sources":["a.js",null],"names":["x"],"mappings":"{mappings}","ignoreList":[{ignore_list}]}}"#
+            )
+            .as_bytes(),
+        );
+        let (line, column) = (rng.next() as u32, rng.next() as u32);
+        exercise(
+            format!(
+                r#"{{"version":3,"sections":[{{"offset":{{"line":{line},"column":{column}}},"map":{{"version":3,"sources":["a.js"],"mappings":"{mappings}","ignoreList":[{ignore_list}]}}}}]}}"#
+            )
+            .as_bytes(),
+        );
+    }
+}
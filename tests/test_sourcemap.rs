@@ -0,0 +1,1740 @@
+use sora::{
+    BorrowedSourceMap, CommentStyle, CompatMode, ConcatError, EncoderState, Mapping, Mappings,
+    MappingsEncoder, ParseError, ParseOptions, ParseWarning, Position, PositionClass,
+    ResolvedMapping, SharedSourceMap, SourceInfo, SourceMap, SourceMapInput, WriteOptions,
+};
+use std::borrow::Cow;
+use std::io::Write;
+
+#[test]
+fn test_to_debug_string() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"names":["foo","bar"],"mappings":"AAAA,IAAIA;;AACA,GCAGC;A"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    insta::assert_snapshot!(sm.to_debug_string(), @r###"
+    SourceMap
+      sources:
+        0: a.js
+        1: b.js
+      names:
+        0: foo
+        1: bar
+      mappings:
+        0:0 -> 0:0:0 [a.js], 0:4 -> 0:0:4 (0) [a.js]
+        2:0 -> 0:1:4 [a.js], 2:3 -> 1:1:7 (1) [b.js]
+        3:0
+    "###);
+}
+
+#[test]
+#[cfg(feature = "dedup")]
+fn test_dedup_names_and_sources() {
+    let mut sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js","a.js"],"sourcesContent":["a",null,"a"],"names":["foo","bar","foo"],"mappings":"AAAAA,CCAAC,CCAAC;AAAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    fn resolve(sm: &SourceMap, col: u32) -> (Option<&str>, Option<&str>) {
+        let mapping = sm.find_mapping((0, col)).unwrap();
+        (
+            mapping.source_id().and_then(|id| sm.source_at(id)),
+            mapping.name_id().and_then(|id| sm.name_at(id)),
+        )
+    }
+    let before = sm.clone();
+
+    sm.dedup_names_and_sources();
+
+    assert_eq!(sm.names().len(), 2);
+    assert_eq!(sm.sources().len(), 2);
+    assert_eq!(sm.sources_content().len(), 2);
+    assert!(sm.validate().is_ok());
+    for col in 0..3 {
+        assert_eq!(resolve(&sm, col), resolve(&before, col));
+    }
+    assert_eq!(resolve(&sm, 2), (Some("a.js"), Some("foo")));
+
+    // an ignored source is kept apart from the same source which is not ignored
+    #[cfg(feature = "ignore_list")]
+    {
+        let mut sm = before.clone();
+        sm.ignore_list_mut().push(2);
+        sm.dedup_names_and_sources();
+        assert_eq!(sm.sources(), before.sources());
+        assert_eq!(sm.ignore_list(), &[2]);
+        for col in 0..3 {
+            assert_eq!(resolve(&sm, col), resolve(&before, col));
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "dedup")]
+fn test_dedup_names_and_sources_memory() {
+    // the size of the entries and the owned strings of `names` and `sources`
+    fn memory(sm: &SourceMap) -> usize {
+        let names = sm.names().iter().map(|name| name.len());
+        let sources = sm
+            .sources()
+            .iter()
+            .map(|source| source.as_ref().map_or(0, |s| s.len()));
+        let entries = std::mem::size_of_val(sm.names()) + std::mem::size_of_val(sm.sources());
+        entries + names.chain(sources).sum::<usize>()
+    }
+
+    let mut sm =
+        SourceMap::from(include_bytes!("../benches/data/tsc.min.js.map").to_vec()).unwrap();
+    let before = sm.clone();
+    sm.dedup_names_and_sources();
+
+    assert_eq!((before.names().len(), sm.names().len()), (25805, 14627));
+    assert_eq!(sm.sources().len(), before.sources().len());
+    assert_eq!((memory(&before), memory(&sm)), (1061642, 640913));
+    for (deduped, mapping) in sm.mappings().iter().zip(before.mappings().iter()) {
+        assert_eq!(
+            deduped.name_id().map(|id| sm.name_at(id)),
+            mapping.name_id().map(|id| before.name_at(id))
+        );
+        assert_eq!(
+            deduped.source_id().map(|id| sm.source_at(id)),
+            mapping.source_id().map(|id| before.source_at(id))
+        );
+    }
+}
+
+#[test]
+fn test_sources_in_generated_range() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA;AACA,EAAA,CCAA;ADAA,ACCA;AACA;AAAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+
+    let range = |start, end| {
+        sm.sources_in_generated_range(start, end)
+            .into_iter()
+            .map(|info| {
+                format!(
+                    "{}:{}:{}",
+                    info.id, info.position.line, info.position.column
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    assert_eq!(range(0, 1), "0:0:0");
+    assert_eq!(range(1, 3), "0:1:0 1:1:0 1:2:0");
+    assert_eq!(range(3, 100), "1:3:0");
+    assert_eq!(range(5, 10), "");
+    assert_eq!(range(2, 1), "");
+}
+
+#[test]
+fn test_write_escaping() {
+    let mut sm =
+        SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#.to_vec()).unwrap();
+    let file = "out \"quoted\" \\ 文件\n.js";
+    let source_root = "C:\\src\\\"root\"/ソース\t/";
+    *sm.file_mut() = Some(Cow::Borrowed(file));
+    *sm.source_root_mut() = Some(Cow::Borrowed(source_root));
+
+    let output = sm.to_string().unwrap();
+    // it must remain valid JSON
+    let value = serde_json::from_str::<serde_json::Value>(&output).unwrap();
+    assert_eq!(value["file"], file);
+    assert_eq!(value["sourceRoot"], source_root);
+
+    let sm = SourceMap::from(output.into_bytes()).unwrap();
+    assert_eq!(sm.file().as_deref(), Some(file));
+    assert_eq!(sm.source_root().as_deref(), Some(source_root));
+}
+
+#[test]
+fn test_sources_content_control_chars() {
+    let mut sm =
+        SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#.to_vec()).unwrap();
+    let content = "a\0b\tc\u{1}d\u{1f}\r\n";
+    sm.sources_content_mut()[0] = Some(Cow::Borrowed(content));
+
+    let output = sm.to_string().unwrap();
+    let value = serde_json::from_str::<serde_json::Value>(&output).unwrap();
+    assert_eq!(value["sourcesContent"][0], content);
+    assert!(!output.bytes().any(|b| b < 0x20));
+
+    let sm = SourceMap::from(output.into_bytes()).unwrap();
+    assert_eq!(sm.sources_content()[0].as_deref(), Some(content));
+}
+
+#[test]
+fn test_find_mapping_empty() {
+    let sm = SourceMap::from(br#"{"version":3}"#.to_vec()).unwrap();
+    assert!(sm.mappings().is_empty());
+    let max = Position::new(u32::MAX, u32::MAX);
+    for pos in [Position::new(0, 0), Position::new(10, 5), max] {
+        assert_eq!(sm.find_mapping(pos), None);
+        assert_eq!(sm.rfind_mapping(pos), None);
+        assert_eq!(sm.finder().find_mapping(pos), None);
+        assert_eq!(sm.caching_finder(4).find_mapping(pos), None);
+        assert_eq!(sm.lookup_table().lookup(pos.line, pos.column), None);
+    }
+
+    // the same finder across queries, including the max position of the initial state
+    let finder = sm.finder();
+    assert_eq!(finder.find_mapping(max), None);
+    assert_eq!(finder.find_mapping((0, 0)), None);
+    assert_eq!(finder.find_mapping((u32::MAX, u32::MAX - 1)), None);
+}
+
+#[test]
+fn test_finder_max_position() {
+    let sm =
+        SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC"}"#.to_vec())
+            .unwrap();
+    let max = Position::new(u32::MAX, u32::MAX);
+    assert_eq!(sm.finder().find_mapping(max), sm.find_mapping(max));
+    assert_eq!(
+        sm.finder().find_mapping(max).unwrap().generated(),
+        Position::new(0, 1)
+    );
+}
+
+#[test]
+fn test_finder_linear_search_backward() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,UAAU,UAAU,UAAU"}"#.to_vec(),
+    )
+    .unwrap();
+    let finder = sm.finder();
+    // moving backward on the same line within a small distance
+    for col in [30, 25, 20, 15, 5, 0] {
+        assert_eq!(finder.find_mapping((0, col)), sm.find_mapping((0, col)));
+    }
+}
+
+#[test]
+fn test_write_inline_sources() {
+    let mut sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["let a",null],"mappings":"AAAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let write = |sm: &SourceMap, options: &WriteOptions| {
+        let mut buf = vec![];
+        sm.write_with_options(&mut buf, options).map(|_| buf)
+    };
+    let mut inline = WriteOptions::default();
+    inline.inline_sources = true;
+
+    // the default writes as is
+    assert_eq!(
+        write(&sm, &WriteOptions::default()).unwrap(),
+        sm.to_vec().unwrap()
+    );
+
+    let err = write(&sm, &inline).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "source #1 has no content to inline");
+
+    sm.sources_content_mut()[1] = Some(Cow::Borrowed("let b"));
+    let output = write(&sm, &inline).unwrap();
+    assert_eq!(
+        output,
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["let a","let b"],"mappings":"AAAA"}"#
+    );
+}
+
+#[test]
+fn test_write_exclude_sources_content() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["let a",null],"mappings":"AAAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let mut options = WriteOptions::default();
+    options.exclude_sources_content = true;
+    let mut buf = vec![];
+    sm.write_with_options(&mut buf, &options).unwrap();
+    assert_eq!(
+        buf,
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA"}"#
+    );
+    // it remains a valid map, without content
+    let sm = SourceMap::from(buf).unwrap();
+    assert_eq!(sm.sources_content(), &[None, None]);
+
+    let mut options = WriteOptions::default();
+    options.inline_sources = true;
+    options.exclude_sources_content = true;
+    let mut buf = vec![];
+    let err = sm.write_with_options(&mut buf, &options).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn test_encode_relative() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"names":["x","y"],"mappings":";AAAA,IAAIA,C;;ACAGC,EAAE,KAAKD;A"}"#
+            .to_vec(),
+    )
+    .unwrap();
+
+    let mut state = EncoderState::new();
+    let mut buf = vec![];
+    for mapping in sm.mappings().iter() {
+        mapping.encode_relative(&mut state, &mut buf).unwrap();
+        assert_eq!(state.generated(), mapping.generated());
+    }
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        sm.mappings().to_encoded_string()
+    );
+
+    // lines can't go backward
+    let mut buf = vec![];
+    let err = Mapping::new(0, 0)
+        .encode_relative(&mut state, &mut buf)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn test_mappings_encoder() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"names":["x","y"],"mappings":";AAAA,IAAIA,C;;ACAGC,EAAE,KAAKD;A"}"#
+            .to_vec(),
+    )
+    .unwrap();
+
+    let mut encoder = MappingsEncoder::new(vec![]);
+    for mapping in sm.mappings().iter() {
+        encoder.write_mapping(mapping).unwrap();
+        assert_eq!(encoder.state().generated(), mapping.generated());
+    }
+    // lines can't go backward, and nothing is written
+    assert!(encoder.write_mapping(&Mapping::new(0, 0)).is_err());
+    assert_eq!(
+        String::from_utf8(encoder.finish().unwrap()).unwrap(),
+        sm.mappings().to_encoded_string()
+    );
+
+    assert!(MappingsEncoder::new(vec![]).finish().unwrap().is_empty());
+}
+
+#[test]
+fn test_mappings_carriage_return() {
+    let sm = SourceMap::from(
+        b"{\"version\":3,\"sources\":[\"a.js\"],\"mappings\":\"AAAA\\r;AACA,\\rCAAC\\r;\\r\"}"
+            .to_vec(),
+    )
+    .unwrap();
+    let expected = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;AACA,CAAC;"}"#.to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.mappings(), expected.mappings());
+}
+
+#[test]
+fn test_shared_source_map() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA,EAAE,ECAC;AACA,EDAE"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let positions = (0..2)
+        .flat_map(|line| (0..6).map(move |col| Position::new(line, col)))
+        .collect::<Vec<_>>();
+    let expected = positions
+        .iter()
+        .map(|&pos| sm.find_mapping(pos))
+        .collect::<Vec<_>>();
+
+    let shared = SharedSourceMap::new(sm);
+    let handles = (0..4)
+        .map(|idx| {
+            let shared = shared.clone();
+            let positions = positions.clone();
+            std::thread::spawn(move || {
+                // each thread creates its own finder, and queries in a different order
+                let finder = shared.finder();
+                let mut found = positions
+                    .iter()
+                    .map(|&pos| (pos, finder.find_mapping(pos)))
+                    .collect::<Vec<_>>();
+                if idx % 2 == 1 {
+                    found.reverse();
+                }
+                found.sort_by_key(|&(pos, _)| pos);
+                found.into_iter().map(|(_, m)| m).collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), expected);
+    }
+    assert_eq!(
+        std::sync::Arc::strong_count(&shared.clone().into_inner()),
+        2
+    );
+}
+
+#[test]
+fn test_caching_finder() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":";EAAA,IAAC,CAAC;;AAAA,MAAM;;IACA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let finder = sm.caching_finder(2);
+
+    let positions = (0..8)
+        .flat_map(|line| (0..8).map(move |column| (line, column)))
+        .collect::<Vec<_>>();
+    // query back and forth to exercise the cache and its eviction
+    for &pos in positions.iter().chain(positions.iter().rev()) {
+        assert_eq!(finder.find_mapping(pos), sm.find_mapping(pos), "{:?}", pos);
+    }
+    for _ in 0..2 {
+        for &pos in &[(4, 7), (1, 0), (6, 4), (1, 5), (4, 0)] {
+            assert_eq!(finder.find_mapping(pos), sm.find_mapping(pos), "{:?}", pos);
+        }
+    }
+}
+
+#[test]
+fn test_version_float() {
+    let parse = |version: &str| {
+        SourceMap::from(format!(r#"{{"version":{},"mappings":""}}"#, version).into_bytes())
+    };
+    assert!(parse("3").is_ok());
+    assert!(parse("3.0").is_ok());
+    assert!(parse("3e0").is_ok());
+    assert!(matches!(parse("3.5"), Err(ParseError::UnsupportedFormat)));
+    assert!(matches!(parse("2"), Err(ParseError::UnsupportedFormat)));
+    assert!(matches!(parse(r#""3""#), Err(ParseError::Syntax(..))));
+}
+
+#[test]
+fn test_max_mappings() {
+    let parse = |max_mappings| {
+        let mut options = ParseOptions::default();
+        options.max_mappings = max_mappings;
+        let mut buf =
+            br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC;AACA,CAAC"}"#.to_vec();
+        BorrowedSourceMap::from_slice_with_options(&mut buf, &options).map(|sm| sm.mappings().len())
+    };
+
+    assert_eq!(parse(None).unwrap(), 4);
+    assert_eq!(parse(Some(4)).unwrap(), 4);
+    assert_eq!(parse(Some(100)).unwrap(), 4);
+    insta::assert_snapshot!(parse(Some(3)).unwrap_err(), @"source map has more than 3 mappings");
+    insta::assert_snapshot!(parse(Some(0)).unwrap_err(), @"source map has more than 0 mappings");
+
+    // the end sentinel doesn't count
+    let parse = |max_mappings, mappings: &str| {
+        let mut options = ParseOptions::default();
+        options.max_mappings = Some(max_mappings);
+        let mut buf =
+            format!(r#"{{"version":3,"sources":["a.js"],"mappings":"{mappings}"}}"#).into_bytes();
+        BorrowedSourceMap::from_slice_with_options(&mut buf, &options).map(|sm| sm.mappings().len())
+    };
+    assert_eq!(parse(1, "AAAA;").unwrap(), 2);
+    assert_eq!(parse(2, "AAAA,CAAC;;").unwrap(), 3);
+    assert!(matches!(
+        parse(1, "AAAA,CAAC;"),
+        Err(ParseError::TooManyMappings { limit: 1 })
+    ));
+}
+
+#[test]
+fn test_unwrap_array() {
+    let mut options = ParseOptions::default();
+    options.unwrap_array = true;
+    let mut buf = br#" [{"version":3,"sources":["a.js"],"mappings":"AAAA"}]"#.to_vec();
+    let sm = BorrowedSourceMap::from_slice_with_options(&mut buf, &options).unwrap();
+    assert_eq!(sm.sources(), &[Some(Cow::Borrowed("a.js"))]);
+    assert_eq!(sm.mappings().len(), 1);
+
+    // a plain map is still accepted
+    let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#.to_vec();
+    assert!(BorrowedSourceMap::from_slice_with_options(&mut buf, &options).is_ok());
+
+    let mut buf = br#"[]"#.to_vec();
+    assert!(matches!(
+        BorrowedSourceMap::from_slice_with_options(&mut buf, &options),
+        Err(ParseError::WrappedArrayLength(0))
+    ));
+    let mut buf = br#"[{"version":3,"mappings":""},{"version":3,"mappings":""}]"#.to_vec();
+    assert!(matches!(
+        BorrowedSourceMap::from_slice_with_options(&mut buf, &options),
+        Err(ParseError::WrappedArrayLength(2))
+    ));
+
+    // disabled by default
+    let mut buf = br#"[{"version":3,"sources":["a.js"],"mappings":"AAAA"}]"#.to_vec();
+    assert!(matches!(
+        BorrowedSourceMap::from_slice_with_options(&mut buf, &ParseOptions::default()),
+        Err(ParseError::Syntax(_))
+    ));
+}
+
+#[test]
+fn test_coerce_invalid_sources() {
+    let json = br#"{"version":3,"sources":["a.js",1,true,{"x":["y",2]},[null,"z"],null,"b.js"],"mappings":"AAAA,CMAA"}"#;
+    let mut options = ParseOptions::default();
+    options.coerce_invalid_sources = true;
+    let mut buf = json.to_vec();
+    let (sm, warnings) =
+        BorrowedSourceMap::from_slice_with_options_and_warnings(&mut buf, &options).unwrap();
+    assert_eq!(
+        sm.sources(),
+        &[
+            Some(Cow::Borrowed("a.js")),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(Cow::Borrowed("b.js")),
+        ]
+    );
+    assert_eq!(
+        warnings,
+        (1..5).map(ParseWarning::InvalidSource).collect::<Vec<_>>()
+    );
+    assert_eq!(sm.mappings()[1].source_id(), Some(6));
+
+    // strict by default
+    let mut buf = json.to_vec();
+    assert!(matches!(
+        BorrowedSourceMap::from_slice(&mut buf),
+        Err(ParseError::InvalidSource(1))
+    ));
+}
+
+#[test]
+fn test_insert_end_sentinel() {
+    let parse = |insert_end_sentinel| {
+        let mut options = ParseOptions::default();
+        options.insert_end_sentinel = insert_end_sentinel;
+        let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC;;"}"#.to_vec();
+        BorrowedSourceMap::from_slice_with_options(&mut buf, &options)
+            .unwrap()
+            .into_owned()
+    };
+
+    let sm = parse(true);
+    assert_eq!(sm.mappings().len(), 3);
+    assert_eq!(sm.find_mapping((2, 0)), Some(Mapping::new(2, 0)));
+    assert_eq!(
+        sm,
+        SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC;;"}"#.to_vec())
+            .unwrap()
+    );
+
+    let sm = parse(false);
+    assert_eq!(sm.mappings().len(), 2);
+    assert_eq!(
+        sm.find_mapping((2, 0)),
+        Some(Mapping::new(0, 1).with_source(0, 0, 1))
+    );
+}
+
+#[test]
+fn test_validate_references_only() {
+    let mut sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"names":["foo"],"mappings":"AAAAA,ECAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(sm.validate_references_only().is_ok());
+
+    // SAFETY: the broken data is never used for findings
+    unsafe {
+        sm.sources_mut2().pop();
+    }
+    insta::assert_snapshot!(sm.validate_references_only().unwrap_err(), @"source map has 1 sources but 2 sourcesContent entries");
+    unsafe {
+        sm.sources_content_mut2().pop();
+    }
+    insta::assert_snapshot!(sm.validate_references_only().unwrap_err(), @"a mapping references unknown source #1");
+    unsafe {
+        sm.sources_mut2().push(Some(Cow::Borrowed("c.js")));
+        sm.sources_content_mut2().push(None);
+        sm.names_mut2().clear();
+    }
+    insta::assert_snapshot!(sm.validate_references_only().unwrap_err(), @"a mapping references unknown name #0");
+    unsafe {
+        sm.names_mut2().push(Cow::Borrowed("bar"));
+    }
+    assert!(sm.validate_references_only().is_ok());
+
+    // the order of mappings is not checked
+    unsafe {
+        sm.mappings_mut().inner_mut().reverse();
+    }
+    assert!(sm.validate_references_only().is_ok());
+    assert!(sm.validate().is_err());
+}
+
+#[test]
+fn test_surrounding_mappings() {
+    // line 1 to 3 are not mapped
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,KAAK;;;;EAIA,IAAI"}"#.to_vec(),
+    )
+    .unwrap();
+    let a = Mapping::new(0, 0).with_source(0, 0, 0);
+    let b = Mapping::new(0, 5).with_source(0, 0, 5);
+    let c = Mapping::new(4, 2).with_source(0, 4, 5);
+    let d = Mapping::new(4, 6).with_source(0, 4, 9);
+
+    assert_eq!(sm.surrounding_mappings((0, 0)), (Some(a), Some(b.clone())));
+    assert_eq!(
+        sm.surrounding_mappings((0, 5)),
+        (Some(b.clone()), Some(c.clone()))
+    );
+    // in the gap
+    for pos in [(0, 6), (1, 0), (2, 10), (4, 1)] {
+        assert_eq!(
+            sm.surrounding_mappings(pos),
+            (Some(b.clone()), Some(c.clone())),
+            "{:?}",
+            pos
+        );
+        assert_eq!(sm.surrounding_mappings(pos).0, sm.find_mapping(pos));
+    }
+    assert_eq!(sm.surrounding_mappings((4, 3)), (Some(c), Some(d.clone())));
+    assert_eq!(sm.surrounding_mappings((4, 6)), (Some(d.clone()), None));
+    assert_eq!(sm.surrounding_mappings((9, 0)), (Some(d), None));
+
+    let sm = SourceMap::from(br#"{"version":3,"mappings":";A"}"#.to_vec()).unwrap();
+    assert_eq!(
+        sm.surrounding_mappings((0, 3)),
+        (None, Some(Mapping::new(1, 0)))
+    );
+}
+
+#[test]
+fn test_original_span() {
+    // 0:0 -> a.js 0:0, 0:4 -> a.js 0:6, 0:8 -> a.js 0:2 (reordered),
+    // 0:10 -> b.js 0:0, 0:12 (no source), 1:0 -> b.js 1:0
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA,IAAM,IAAJ,ECAF,E;AACA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let info = |id, line, column| SourceInfo::new(id, Position::new(line, column));
+
+    assert_eq!(
+        sm.original_span((0, 0)),
+        Some((info(0, 0, 0), info(0, 0, 6)))
+    );
+    assert_eq!(
+        sm.original_span((0, 3)),
+        Some((info(0, 0, 0), info(0, 0, 6)))
+    );
+    // the following mapping precedes in the source
+    assert_eq!(sm.original_span((0, 4)), None);
+    // no following mapping in the same source
+    assert_eq!(sm.original_span((0, 9)), None);
+    // the following mapping without source is skipped
+    assert_eq!(
+        sm.original_span((0, 11)),
+        Some((info(1, 0, 0), info(1, 1, 0)))
+    );
+    // the mapping has no source
+    assert_eq!(sm.original_span((0, 12)), None);
+    // no following mapping
+    assert_eq!(sm.original_span((1, 0)), None);
+
+    // 0:0 -> a.js 0:0, 0:2 -> b.js 0:0, 0:4 -> a.js 0:2
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA,ECAA,EDAE"}"#.to_vec(),
+    )
+    .unwrap();
+    // the following mapping in another source is skipped
+    assert_eq!(
+        sm.original_span((0, 1)),
+        Some((info(0, 0, 0), info(0, 0, 2)))
+    );
+    assert_eq!(sm.original_span((0, 2)), None);
+}
+
+#[test]
+fn test_canonicalize() {
+    let parse = |mappings: &str| {
+        SourceMap::from(
+            format!(
+                r#"{{"version":3,"sources":["a.js"],"names":["x"],"mappings":"{}"}}"#,
+                mappings
+            )
+            .into_bytes(),
+        )
+        .unwrap()
+    };
+
+    let mut a = parse("AAAA,CAAA,CAAA;AACA,EAAA,CAAAA,CAAAA,C");
+    let mut b = parse("AAAA;AACA,GAAAA,E");
+    assert_ne!(a.to_vec().unwrap(), b.to_vec().unwrap());
+    a.canonicalize();
+    b.canonicalize();
+    assert_eq!(a.to_vec().unwrap(), b.to_vec().unwrap());
+    assert_eq!(a.mappings().to_encoded_string(), "AAAA;AACA,GAAAA,E");
+
+    // idempotent
+    let expected = a.to_vec().unwrap();
+    a.canonicalize();
+    assert_eq!(a.to_vec().unwrap(), expected);
+}
+
+#[test]
+fn test_take_mappings() {
+    let mut sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC;AACA"}"#.to_vec(),
+    )
+    .unwrap();
+    let expected = sm.mappings().clone();
+    let ptr = sm.mappings().as_ptr();
+
+    let mappings = sm.take_mappings();
+    assert_eq!(mappings, expected);
+    // moved without copying
+    assert_eq!(mappings.as_ptr(), ptr);
+    assert!(sm.mappings().is_empty());
+    assert_eq!(sm.find_mapping((0, 1)), None);
+    sm.validate().unwrap();
+}
+
+#[test]
+fn test_has_mappings() {
+    let parse = |mappings: &str| {
+        SourceMap::from(
+            format!(
+                r#"{{"version":3,"sources":["a.js"],"mappings":"{}"}}"#,
+                mappings
+            )
+            .into_bytes(),
+        )
+        .unwrap()
+    };
+    assert!(!parse("").has_mappings());
+    // only generated-only segments and the end sentinel
+    let sm = parse("A,C;E;;");
+    assert_eq!(sm.mappings().len(), 4);
+    assert!(!sm.has_mappings());
+    assert!(parse("A,CAAA").has_mappings());
+}
+
+#[test]
+fn test_source_transitions() {
+    // two modules concatenated, with a generated-only mapping between them
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA,EAAE;AACA;A,ECAC,EAAE;AACA,GDCA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let transitions = sm.mappings().source_transitions().collect::<Vec<_>>();
+    assert_eq!(
+        transitions,
+        vec![
+            (Position::new(0, 0), Some(0)),
+            (Position::new(2, 0), None),
+            (Position::new(2, 2), Some(1)),
+            (Position::new(3, 3), Some(0)),
+        ]
+    );
+
+    // each region is mapped to a single source
+    for (idx, &(start, source_id)) in transitions.iter().enumerate() {
+        let end = transitions.get(idx + 1).map(|&(pos, _)| pos);
+        assert!(sm
+            .mappings()
+            .iter()
+            .filter(|m| m.generated() >= start && !end.is_some_and(|end| m.generated() >= end))
+            .all(|m| m.source_id() == source_id));
+    }
+
+    assert_eq!(sora::Mappings::default().source_transitions().count(), 0);
+}
+
+#[test]
+fn test_generated_line_count() {
+    let count = |mappings: &str| {
+        SourceMap::from(
+            format!(
+                r#"{{"version":3,"sources":["a.js"],"mappings":"{}"}}"#,
+                mappings
+            )
+            .into_bytes(),
+        )
+        .unwrap()
+        .generated_line_count()
+    };
+    assert_eq!(count(""), 0);
+    assert_eq!(count(";;"), 0);
+    assert_eq!(count("AAAA,CAAC,CAAC"), 1);
+    assert_eq!(count(";;AAAA;CAAC"), 4);
+    // including the end sentinel
+    assert_eq!(count("AAAA;;"), 3);
+}
+
+#[test]
+fn test_generated_for_source_line() {
+    // a.js line 0 is generated to line 0 and line 2
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"mappings":"AAAA,EAAE,ECAA;AACA,EDAA,I;AADA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        sm.generated_for_source_line(0, 0),
+        vec![
+            Position::new(0, 0),
+            Position::new(0, 2),
+            Position::new(2, 0)
+        ]
+    );
+    assert_eq!(
+        sm.generated_for_source_line(1, 0),
+        vec![Position::new(0, 4)]
+    );
+    assert_eq!(
+        sm.generated_for_source_line(1, 1),
+        vec![Position::new(1, 0)]
+    );
+    assert_eq!(
+        sm.generated_for_source_line(0, 1),
+        vec![Position::new(1, 2)]
+    );
+    assert!(sm.generated_for_source_line(0, 5).is_empty());
+    assert!(sm.generated_for_source_line(2, 0).is_empty());
+}
+
+#[test]
+fn test_utf16_columns() {
+    // "é" takes 2 bytes, "你" 3 bytes, and "😀" 4 bytes in UTF-8, which is 2 code units in UTF-16
+    let sm = SourceMap::from(
+        r#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["// ascii\r\nf('é', '你', '😀', x);\n",null],"mappings":""}"#
+            .as_bytes()
+            .to_vec(),
+    )
+    .unwrap();
+
+    // ascii line
+    assert_eq!(sm.utf16_to_utf8_column(0, 0, 3), Some(3));
+    assert_eq!(sm.utf8_to_utf16_column(0, 0, 8), Some(8));
+    // the `\r` is not part of the line
+    assert_eq!(sm.utf16_to_utf8_column(0, 0, 9), None);
+
+    let utf16_to_utf8 = [
+        (0, 0),
+        (3, 3),
+        (4, 5),
+        (8, 9),
+        (9, 12),
+        (13, 16),
+        (15, 20),
+        (21, 26),
+    ];
+    for (utf16, utf8) in utf16_to_utf8 {
+        assert_eq!(
+            sm.utf16_to_utf8_column(0, 1, utf16),
+            Some(utf8),
+            "{}",
+            utf16
+        );
+        assert_eq!(sm.utf8_to_utf16_column(0, 1, utf8), Some(utf16), "{}", utf8);
+    }
+    // the middle of the surrogate pair of "😀"
+    assert_eq!(sm.utf16_to_utf8_column(0, 1, 14), None);
+    // the middle of the bytes of "é"
+    assert_eq!(sm.utf8_to_utf16_column(0, 1, 4), None);
+    // beyond the end of the line
+    assert_eq!(sm.utf16_to_utf8_column(0, 1, 22), None);
+    assert_eq!(sm.utf8_to_utf16_column(0, 1, 27), None);
+    // the last empty line
+    assert_eq!(sm.utf16_to_utf8_column(0, 2, 0), Some(0));
+    // no such line, no content, no source
+    assert_eq!(sm.utf16_to_utf8_column(0, 3, 0), None);
+    assert_eq!(sm.utf16_to_utf8_column(1, 0, 0), None);
+    assert_eq!(sm.utf8_to_utf16_column(2, 0, 0), None);
+}
+
+#[test]
+fn test_find_mapping_at_offset() {
+    // `const a = 1;\nfoo(a, "é", b);`
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,MAAM;AACA,IAAI,GAAG,KAAK"}"#.to_vec(),
+    )
+    .unwrap();
+    let code = "const a = 1;\r\nfoo(a, \"é\", b);";
+    let find = |offset| {
+        sm.find_mapping_at_offset(code, offset)
+            .map(|m| (m.generated().line, m.generated().column))
+    };
+    assert_eq!(find(0), Some((0, 0)));
+    assert_eq!(find(7), Some((0, 6)));
+    // the offset of `foo`
+    assert_eq!(find(14), Some((1, 0)));
+    assert_eq!(find(18), Some((1, 4)));
+    // `b` is after `é`, which takes 2 bytes but 1 UTF-16 code unit
+    let b = code.rfind('b').unwrap();
+    assert_eq!(find(b), Some((1, 12)));
+    assert_eq!(find(code.len()), Some((1, 12)));
+
+    // in the middle of `é`, and beyond the end
+    assert_eq!(find(code.find('é').unwrap() + 1), None);
+    assert_eq!(find(code.len() + 1), None);
+}
+
+#[test]
+fn test_lookup_table() {
+    let table = {
+        let sm = SourceMap::from(
+            br#"{"version":3,"sources":["a.js"],"mappings":";EAAA,IAAC,CAAC;;AAAA,MAAM;;IACA"}"#
+                .to_vec(),
+        )
+        .unwrap();
+        let table = sm.lookup_table();
+        for line in 0..8 {
+            for column in 0..8 {
+                assert_eq!(
+                    table.lookup(line, column),
+                    sm.find_mapping((line, column)),
+                    "{:?}",
+                    (line, column)
+                );
+            }
+        }
+        table
+    };
+    // the table outlives the source map
+    assert_eq!(table.lookup(3, 7).unwrap().generated(), Position::new(3, 6));
+
+    let empty = SourceMap::from(br#"{"version":3,"mappings":""}"#.to_vec())
+        .unwrap()
+        .lookup_table();
+    assert_eq!(empty.lookup(0, 0), None);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_from_json_value() {
+    let json = r#"{"version":3,"file":"a.min.js","sources":["a.js",null],"sourcesContent":["a",null],"names":["foo"],"mappings":"AAAAA,CCAA"}"#;
+    let value: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        SourceMap::from_json_value(&value).unwrap(),
+        SourceMap::from(json.as_bytes().to_vec()).unwrap()
+    );
+
+    let value: serde_json::Value =
+        serde_json::from_str(r#"{"version":3,"names":[1],"mappings":""}"#).unwrap();
+    insta::assert_snapshot!(SourceMap::from_json_value(&value).unwrap_err(), @"source map syntax error: field `names` has an invalid type");
+    let value: serde_json::Value = serde_json::from_str(r#"[]"#).unwrap();
+    insta::assert_snapshot!(SourceMap::from_json_value(&value).unwrap_err(), @"source map syntax error: expected a JSON object");
+}
+
+#[test]
+#[cfg(feature = "json-comments")]
+fn test_parse_with_comments() {
+    let sm = SourceMap::from(
+        br#"{
+            // hand-edited
+            "version": 3,
+            "sources": ["a.js" /* renamed from "b.js" */],
+            "mappings": "AAAA" // "AACA"
+        }"#
+        .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.source_at(0), Some("a.js"));
+    assert_eq!(sm.mappings().len(), 1);
+}
+
+#[test]
+fn test_split_by_source() {
+    // sum.js, concatenated from add.js and mul.js
+    let sm = SourceMap::from(
+        br#"{"version":3,"file":"sum.js","sources":["add.js","mul.js"],"sourcesContent":["add",null],"names":["add","a","mul"],"mappings":"AAAAA,IAAIC;ACAJC,IAAID;ADAAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+
+    let split = sm.split_by_source();
+    assert_eq!(split.len(), 2);
+
+    let (id, add) = &split[0];
+    assert_eq!(*id, 0);
+    assert_eq!(add.file().as_deref(), Some("sum.js"));
+    assert_eq!(add.sources(), &[Some(Cow::Borrowed("add.js"))]);
+    assert_eq!(add.sources_content(), &[Some(Cow::Borrowed("add"))]);
+    assert_eq!(add.names(), &[Cow::Borrowed("add"), Cow::Borrowed("a")]);
+    assert!(add.validate().is_ok());
+    insta::assert_snapshot!(format!("{:?}", add.mappings().iter().collect::<Vec<_>>()), @"[0:0 -> 0:0:0 (0), 0:4 -> 0:0:4 (1), 2:0 -> 0:0:4 (1)]");
+
+    let (id, mul) = &split[1];
+    assert_eq!(*id, 1);
+    assert_eq!(mul.sources(), &[Some(Cow::Borrowed("mul.js"))]);
+    assert_eq!(mul.sources_content(), &[None]);
+    assert_eq!(mul.names(), &[Cow::Borrowed("mul"), Cow::Borrowed("a")]);
+    assert!(mul.validate().is_ok());
+    insta::assert_snapshot!(format!("{:?}", mul.mappings().iter().collect::<Vec<_>>()), @"[1:0 -> 0:0:0 (0), 1:4 -> 0:0:4 (1)]");
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+fn test_parse_metrics() {
+    let mut buf = br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,CAAC;AACA"}"#.to_vec();
+    let len = buf.len();
+    let (sm, metrics) = sora::BorrowedSourceMap::from_slice_with_metrics(&mut buf).unwrap();
+    assert_eq!(metrics.bytes, len);
+    assert_eq!(metrics.mappings, sm.mappings().len());
+    assert_eq!(metrics.mappings, 3);
+}
+
+#[test]
+#[cfg(feature = "digest")]
+fn test_content_digest() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["const a = 1;\n",null],"mappings":""}"#
+            .to_vec(),
+    )
+    .unwrap();
+    // `printf 'const a = 1;\n' | sha256sum`
+    let hex = sm
+        .content_digest(0)
+        .unwrap()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    assert_eq!(
+        hex,
+        "b79b14bd2584dd52b0f0ef042a2a4f104cda48330500e12237737cc51fbda43d"
+    );
+    assert_eq!(sm.content_digest(1), None);
+    assert_eq!(sm.content_digest(2), None);
+}
+
+#[test]
+fn test_sort_sources() {
+    let mut sm = SourceMap::from(
+        br#"{"version":3,"sources":["c.js","a.js",null,"b.js"],"sourcesContent":["c","a",null,null],"names":["x"],"mappings":"AAAA,CCAC,CCACA;AACA,CCAC","ignoreList":[0,3,0]}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let positions = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1)];
+    let before = positions
+        .iter()
+        .map(|&pos| {
+            let mapping = sm.find_mapping(pos).unwrap();
+            let source_id = mapping.source_id().unwrap();
+            (
+                sm.source_at(source_id).map(str::to_owned),
+                sm.source_content_at(source_id).map(str::to_owned),
+                mapping.source_position(),
+                mapping.name_id(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    sm.sort_sources();
+    assert_eq!(
+        sm.sources(),
+        [
+            None,
+            Some("a.js".into()),
+            Some("b.js".into()),
+            Some("c.js".into())
+        ]
+    );
+    assert_eq!(
+        sm.sources_content(),
+        [None, Some("a".into()), None, Some("c".into())]
+    );
+    // c.js and b.js, remapped in order and without the duplicate
+    #[cfg(feature = "ignore_list")]
+    assert_eq!(sm.ignore_list(), &[2, 3]);
+    sm.validate().unwrap();
+
+    let after = positions
+        .iter()
+        .map(|&pos| {
+            let mapping = sm.find_mapping(pos).unwrap();
+            let source_id = mapping.source_id().unwrap();
+            (
+                sm.source_at(source_id).map(str::to_owned),
+                sm.source_content_at(source_id).map(str::to_owned),
+                mapping.source_position(),
+                mapping.name_id(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(before, after);
+
+    // already sorted
+    let sorted = sm.clone();
+    sm.sort_sources();
+    assert_eq!(sm, sorted);
+}
+
+#[test]
+fn test_mapped_coverage() {
+    // lines 0 and 4 have source info, line 1 has only a mapping without source,
+    // lines 2 and 3 are empty
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":"AAAA,EAAE;A;;;AACA"}"#.to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.generated_line_count(), 5);
+    assert_eq!(sm.mapped_coverage(), 0.4);
+
+    let sm = SourceMap::from(br#"{"version":3,"sources":[],"mappings":""}"#.to_vec()).unwrap();
+    assert_eq!(sm.mapped_coverage(), 0.0);
+}
+
+#[test]
+fn test_from_input() {
+    let json = br#"{"version":3,"file":"out.js","sourceRoot":"src","sources":["a.js",null],"sourcesContent":["let a",null],"names":["a"],"mappings":"AAAAA,CCAC;AACA"}"#;
+    let sm = SourceMap::from_input(SourceMapInput {
+        file: Some("out.js"),
+        source_root: Some("src"),
+        sources: vec![Some("a.js"), None],
+        sources_content: Some(vec![Some("let a"), None]),
+        names: vec!["a"],
+        mappings: "AAAAA,CCAC;AACA",
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(sm, SourceMap::from(json.to_vec()).unwrap());
+
+    assert!(matches!(
+        SourceMap::from_input(SourceMapInput {
+            mappings: "AAAA",
+            ..Default::default()
+        }),
+        Err(ParseError::UnknownSourceReference(0))
+    ));
+    assert!(matches!(
+        SourceMap::from_input(SourceMapInput {
+            sources: vec![Some("a.js")],
+            sources_content: Some(vec![]),
+            ..Default::default()
+        }),
+        Err(ParseError::MismatchSourcesContent { .. })
+    ));
+    assert!(matches!(
+        SourceMap::from_input(SourceMapInput {
+            version: 2,
+            ..Default::default()
+        }),
+        Err(ParseError::UnsupportedFormat)
+    ));
+}
+
+#[test]
+fn test_source_line_text() {
+    let sm = SourceMap::from(
+        r#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["// 🦀🦀\nlet s = '🎉';\r\n\n😀",null],"mappings":""}"#
+            .into(),
+    )
+    .unwrap();
+    assert_eq!(sm.source_line_text(0, 0), Some("// 🦀🦀"));
+    assert_eq!(sm.source_line_text(0, 1), Some("let s = '🎉';"));
+    assert_eq!(sm.source_line_text(0, 2), Some(""));
+    assert_eq!(sm.source_line_text(0, 3), Some("😀"));
+    assert_eq!(sm.source_line_text(0, 4), None);
+    assert_eq!(sm.source_line_text(0, u32::MAX), None);
+    assert_eq!(sm.source_line_text(1, 0), None);
+    assert_eq!(sm.source_line_text(2, 0), None);
+}
+
+#[test]
+fn test_content_is_ascii() {
+    let sm = SourceMap::from(
+        r#"{"version":3,"sources":["a.js","b.js","c.js"],"sourcesContent":["let a = 1;\n","let s = '🦀';",null],"mappings":""}"#
+            .into(),
+    )
+    .unwrap();
+    assert_eq!(sm.content_is_ascii(0), Some(true));
+    assert_eq!(sm.content_is_ascii(1), Some(false));
+    assert_eq!(sm.content_is_ascii(2), None);
+    assert_eq!(sm.content_is_ascii(3), None);
+    assert!(!sm.all_content_ascii());
+
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["let a = 1;",null],"mappings":""}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(sm.all_content_ascii());
+}
+
+#[test]
+fn test_file_in_sources() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"file":"out.js","sources":["a.js",null,"out.js"],"mappings":""}"#.to_vec(),
+    )
+    .unwrap();
+    assert!(sm.file_in_sources());
+
+    let sm = SourceMap::from(
+        br#"{"version":3,"file":"out.js","sources":["a.js",null,"src/out.js"],"mappings":""}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(!sm.file_in_sources());
+
+    let sm = SourceMap::from(br#"{"version":3,"sources":[null],"mappings":""}"#.to_vec()).unwrap();
+    assert!(!sm.file_in_sources());
+}
+
+#[test]
+fn test_diff() {
+    let old = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js","c.js"],"names":["x"],"mappings":"AAAAA,ECAA;ACAA,EAAE"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(old.diff(&old).is_empty());
+
+    // the same mappings with renumbered sources
+    let reordered = SourceMap::from(
+        br#"{"version":3,"sources":["c.js","b.js","a.js"],"names":["x"],"mappings":"AEAAA,EDAA;ADAA,EAAE"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(old.diff(&reordered).is_empty());
+
+    // `c.js` is replaced with `d.js`, and the last mapping points to another column
+    let new = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js","d.js"],"names":["x"],"mappings":"AAAAA,ECAA;ACAA,EAAG"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let diff = old.diff(&new);
+    assert_eq!(diff.added_sources, ["d.js"]);
+    assert_eq!(diff.removed_sources, ["c.js"]);
+    assert_eq!(diff.changed_mappings, 4);
+    assert_eq!(diff.first_divergence, Some(Position::new(1, 0)));
+    assert!(!diff.is_empty());
+
+    // a mapping is dropped, so the lookups diverge from its position
+    let new = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js","c.js"],"names":["x"],"mappings":"AAAAA;AEAA,EAAE"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let diff = old.diff(&new);
+    assert!(diff.added_sources.is_empty() && diff.removed_sources.is_empty());
+    assert_eq!(diff.changed_mappings, 1);
+    assert_eq!(diff.first_divergence, Some(Position::new(0, 2)));
+}
+
+#[test]
+fn test_compat_mode() {
+    // no `version`, and `sourcesContent` is shorter than `sources`
+    let json = br#"{"sources":["a.js","b.js"],"sourcesContent":["a"],"mappings":"AAAA,CCAA"}"#;
+    let parse = |json: &[u8], mode| {
+        BorrowedSourceMap::from_slice_with_mode(&mut json.to_vec(), mode).map(|sm| sm.into_owned())
+    };
+
+    assert!(matches!(
+        parse(json, CompatMode::Strict),
+        Err(ParseError::UnsupportedFormat)
+    ));
+    assert!(matches!(
+        parse(json, CompatMode::Node),
+        Err(ParseError::MismatchSourcesContent {
+            sources_len: 2,
+            sources_content_len: 1
+        })
+    ));
+    let sm = parse(json, CompatMode::Chrome).unwrap();
+    assert_eq!(sm.sources_content(), [Some("a".into()), None]);
+    assert_eq!(sm.mappings().len(), 2);
+    sm.validate().unwrap();
+
+    // extra entries are dropped
+    let json = br#"{"sources":["a.js"],"sourcesContent":["a","b"],"mappings":"AAAA"}"#;
+    let sm = parse(json, CompatMode::Chrome).unwrap();
+    assert_eq!(sm.sources_content(), [Some("a".into())]);
+
+    // only the version is lenient in node
+    let json = br#"{"sources":["a.js"],"sourcesContent":["a"],"mappings":"AAAA"}"#;
+    assert!(parse(json, CompatMode::Strict).is_err());
+    assert_eq!(
+        parse(json, CompatMode::Node).unwrap(),
+        parse(json, CompatMode::Chrome).unwrap()
+    );
+
+    // a well-formed map is parsed the same in every mode
+    let json = br#"{"version":3,"sources":["a.js"],"sourcesContent":["a"],"mappings":"AAAA"}"#;
+    let expected = SourceMap::from(json.to_vec()).unwrap();
+    for mode in [CompatMode::Strict, CompatMode::Chrome, CompatMode::Node] {
+        assert_eq!(parse(json, mode).unwrap(), expected);
+    }
+    // but an unsupported version is rejected by all
+    let json = br#"{"version":2,"sources":[],"mappings":""}"#;
+    for mode in [CompatMode::Strict, CompatMode::Chrome, CompatMode::Node] {
+        assert!(matches!(
+            parse(json, mode),
+            Err(ParseError::UnsupportedFormat)
+        ));
+    }
+}
+
+#[test]
+fn test_resize_sources_content_warning() {
+    let mut buf =
+        br#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":[],"mappings":""}"#.to_vec();
+    let mut options = ParseOptions::default();
+    options.resize_sources_content = true;
+    let (sm, warnings) =
+        BorrowedSourceMap::from_slice_with_options_and_warnings(&mut buf, &options).unwrap();
+    assert_eq!(sm.sources_content(), [None, None]);
+    assert_eq!(
+        warnings,
+        vec![ParseWarning::MismatchSourcesContent {
+            sources_len: 2,
+            sources_content_len: 0
+        }]
+    );
+}
+
+#[test]
+fn test_resolved_mappings() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sourceRoot":"src","sources":["a.js",null],"names":["x","y"],"mappings":"AAAAA,C,CCACC;AACAD"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let resolved = sm.resolved_mappings().collect::<Vec<_>>();
+    assert_eq!(resolved.len(), sm.mappings().len());
+    for (resolved, mapping) in resolved.iter().zip(sm.mappings().iter()) {
+        assert_eq!(resolved.generated, mapping.generated());
+        assert_eq!(
+            resolved.source,
+            mapping
+                .source_id()
+                .and_then(|id| sm.sources()[id as usize].as_deref())
+        );
+        assert_eq!(resolved.original, mapping.source_position());
+        assert_eq!(
+            resolved.name,
+            mapping.name_id().map(|id| sm.names()[id as usize].as_ref())
+        );
+    }
+
+    assert_eq!(
+        resolved[0],
+        ResolvedMapping {
+            generated: Position::new(0, 0),
+            source: Some("a.js"),
+            original: Some(Position::new(0, 0)),
+            name: Some("x"),
+        }
+    );
+    // without source information
+    assert_eq!(resolved[1].source, None);
+    assert_eq!(resolved[1].original, None);
+    // with a `null` source
+    assert_eq!(resolved[2].source, None);
+    assert_eq!(resolved[2].original, Some(Position::new(0, 1)));
+    assert_eq!(resolved[2].name, Some("y"));
+}
+
+#[test]
+fn test_as_borrowed() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"file":"out.js","sourceRoot":"src","sources":["a.js"],"sourcesContent":["a"],"names":["foo"],"mappings":"AAAAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let borrowed = sm.as_borrowed();
+
+    assert!(matches!(borrowed.file(), Some(Cow::Borrowed(_))));
+    assert!(matches!(borrowed.source_root(), Some(Cow::Borrowed(_))));
+    assert!(matches!(borrowed.sources()[0], Some(Cow::Borrowed(_))));
+    assert!(matches!(
+        borrowed.sources_content()[0],
+        Some(Cow::Borrowed(_))
+    ));
+    assert!(matches!(borrowed.names()[0], Cow::Borrowed(_)));
+    // the strings are shared
+    assert_eq!(
+        borrowed.source_at(0).unwrap().as_ptr(),
+        sm.source_at(0).unwrap().as_ptr()
+    );
+    assert_eq!(
+        borrowed.name_at(0).unwrap().as_ptr(),
+        sm.name_at(0).unwrap().as_ptr()
+    );
+
+    assert_eq!(borrowed.to_vec().unwrap(), sm.to_vec().unwrap());
+    // unlike the strings, the mappings are copied
+    assert_ne!(borrowed.mappings().as_ptr(), sm.mappings().as_ptr());
+
+    // a reference needs no conversion
+    fn first_name<'a>(sm: &'a BorrowedSourceMap<'a>) -> Option<&'a str> {
+        sm.name_at(0)
+    }
+    assert_eq!(first_name(&sm), Some("foo"));
+}
+
+#[test]
+fn test_eq() {
+    let mut buf = br#"{"version":3,"file":"out.js","sources":["a.js","b\tjs"],"names":["foo"],"mappings":"AAAAA,CCAA"}"#.to_vec();
+    let sm = sora::BorrowedSourceMap::from_slice(&mut buf).unwrap();
+    let owned = sm.clone().into_owned();
+    assert_eq!(sm, owned.as_borrowed());
+    assert_eq!(sm, owned);
+
+    let mut other = owned.clone();
+    other.names_mut()[0] = Cow::Borrowed("bar");
+    assert_ne!(sm, other);
+}
+
+#[test]
+fn test_into_owned_reuses_owned() {
+    let mut buf = br#"{"version":3,"file":"out.js","sources":["a.js",null],"sourcesContent":["let a",null],"names":["foo"],"mappings":"AAAAA"}"#.to_vec();
+    let mut sm = BorrowedSourceMap::from_slice(&mut buf).unwrap();
+    *sm.file_mut() = Some(Cow::Owned("owned.js".to_owned()));
+    let file_ptr = sm.file().as_deref().unwrap().as_ptr();
+    let borrowed = sm.clone();
+
+    let owned = sm.into_owned();
+    // the owned strings are moved, and the borrowed ones are cloned
+    assert_eq!(owned.file().as_deref().unwrap().as_ptr(), file_ptr);
+    assert_ne!(
+        owned.source_at(0).unwrap().as_ptr(),
+        borrowed.source_at(0).unwrap().as_ptr()
+    );
+    assert_eq!(owned, borrowed);
+
+    // an already owned map is moved as is
+    let ptrs = |sm: &SourceMap| {
+        (
+            sm.source_at(0).unwrap().as_ptr(),
+            sm.source_content_at(0).unwrap().as_ptr(),
+            sm.name_at(0).unwrap().as_ptr(),
+        )
+    };
+    let expected = ptrs(&owned);
+    let again = owned.clone();
+    let moved = owned.into_owned();
+    assert_eq!(ptrs(&moved), expected);
+    assert_eq!(moved, again);
+}
+
+#[test]
+fn test_write_streaming() {
+    struct CountingWriter {
+        total: usize,
+        writes: usize,
+        max_write: usize,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.total += buf.len();
+            self.writes += 1;
+            self.max_write = self.max_write.max(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let content = "x".repeat(4096);
+    let mut sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js","c.js"],"mappings":"AAAA;ACAA;ACAA"}"#.to_vec(),
+    )
+    .unwrap();
+    for source_content in unsafe { sm.sources_content_mut2() } {
+        *source_content = Some(Cow::Owned(content.clone()));
+    }
+
+    let mut writer = CountingWriter {
+        total: 0,
+        writes: 0,
+        max_write: 0,
+    };
+    sm.write(&mut writer).unwrap();
+
+    assert_eq!(writer.total, sm.to_vec().unwrap().len());
+    assert!(writer.writes > 1);
+    // no more than a single element (with its quotes) is buffered at a time
+    assert!(writer.max_write <= content.len() + 2);
+}
+
+#[test]
+fn test_fill_content_from() {
+    let mut sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js","b.js","c.js",null],"sourcesContent":[null,"kept",null,null],"mappings":"AAAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let other = SourceMap::from(
+        br#"{"version":3,"sources":["c.js","b.js","a.js","d.js"],"sourcesContent":[null,"replaced","let a = 1;","let d"],"mappings":""}"#
+            .to_vec(),
+    )
+    .unwrap();
+
+    sm.fill_content_from(&other);
+    assert_eq!(
+        sm.sources_content(),
+        &[
+            Some(Cow::Borrowed("let a = 1;")),
+            Some(Cow::Borrowed("kept")),
+            None,
+            None,
+        ]
+    );
+    sm.validate().unwrap();
+}
+
+#[test]
+fn test_map_sources() {
+    let mut buf =
+        br#"{"version":3,"sources":["src\\a.js",null,"b.js"],"names":["x"],"mappings":""}"#
+            .to_vec();
+    let mut sm = sora::BorrowedSourceMap::from_slice(&mut buf).unwrap();
+
+    let mut visited = 0;
+    sm.map_sources(|source| {
+        visited += 1;
+        if source.contains('\\') {
+            Cow::Owned(source.replace('\\', "/"))
+        } else {
+            Cow::Borrowed(source)
+        }
+    });
+    // `None` sources are skipped
+    assert_eq!(visited, 2);
+    assert_eq!(sm.source_at(0), Some("src/a.js"));
+    assert_eq!(sm.source_at(1), None);
+    // unchanged sources keep borrowing
+    assert!(matches!(sm.sources()[2], Some(Cow::Borrowed("b.js"))));
+
+    sm.map_names(|name| Cow::Borrowed(&name[..0]));
+    assert_eq!(sm.name_at(0), Some(""));
+}
+
+#[test]
+fn test_concat() {
+    let mut a = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"sourcesContent":["a"],"names":["x"],"mappings":"AAAAA;"}"#.to_vec(),
+    )
+    .unwrap();
+    let b = SourceMap::from(
+        br#"{"version":3,"sources":["b.js"],"names":["y"],"mappings":"AAAAA,CAAC"}"#.to_vec(),
+    )
+    .unwrap();
+
+    // the end marker of `a` at 1:0 is replaced
+    a.concat(&b, 1).unwrap();
+    assert!(a.validate().is_ok());
+    assert_eq!(a.sources_content(), &[Some(Cow::Borrowed("a")), None]);
+    insta::assert_snapshot!(a.to_debug_string(), @r###"
+    SourceMap
+      sources:
+        0: a.js
+        1: b.js
+      names:
+        0: x
+        1: y
+      mappings:
+        0:0 -> 0:0:0 (0) [a.js]
+        1:0 -> 1:0:0 (1) [b.js], 1:1 -> 1:0:1 [b.js]
+    "###);
+}
+
+#[test]
+fn test_merge_names() {
+    let mut a = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"names":["x","y"],"mappings":"AAAAA,CAAAC"}"#.to_vec(),
+    )
+    .unwrap();
+    // the names overlap by id but not by meaning
+    let b = SourceMap::from(
+        br#"{"version":3,"sources":["b.js"],"names":["y","z"],"mappings":"AAAAC,CAAAD"}"#.to_vec(),
+    )
+    .unwrap();
+    let resolve = |sm: &SourceMap, mappings: &Mappings| {
+        mappings
+            .iter()
+            .filter_map(|m| m.name_id())
+            .map(|id| sm.name_at(id).unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    let expected = resolve(&b, b.mappings());
+    assert_eq!(expected, ["z", "y"]);
+
+    let mappings = a.merge_names(&b).unwrap();
+    assert_eq!(a.names(), &["x", "y", "y", "z"]);
+    assert_eq!(resolve(&a, &mappings), expected);
+    assert_eq!(resolve(&a, a.mappings()), ["x", "y"]);
+}
+
+#[test]
+fn test_concat_overlap() {
+    let mut a =
+        SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA;AAAA"}"#.to_vec())
+            .unwrap();
+    let b =
+        SourceMap::from(br#"{"version":3,"sources":["b.js"],"mappings":"AAAA"}"#.to_vec()).unwrap();
+
+    let err = a.concat(&b, 1).unwrap_err();
+    assert!(matches!(
+        err,
+        ConcatError::Overlap {
+            last: Position { line: 1, column: 0 },
+            first: Position { line: 1, column: 0 },
+        }
+    ));
+    // nothing is changed
+    assert_eq!(a.sources().len(), 1);
+    assert_eq!(a.mappings().len(), 2);
+
+    a.concat(&b, 2).unwrap();
+    assert_eq!(a.mappings().len(), 3);
+}
+
+#[test]
+fn test_contains_position() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":";;EAAA,EAAE;;EACA"}"#.to_vec(),
+    )
+    .unwrap();
+
+    // before
+    assert!(!sm.contains_position((0, 0)));
+    assert!(!sm.contains_position((2, 1)));
+    // inside
+    assert!(sm.contains_position((2, 2)));
+    assert!(sm.contains_position((3, 100)));
+    assert!(sm.contains_position((4, 2)));
+    // after
+    assert!(!sm.contains_position((4, 3)));
+    assert!(!sm.contains_position((5, 0)));
+
+    let empty = SourceMap::from(br#"{"version":3,"mappings":""}"#.to_vec()).unwrap();
+    assert!(!empty.contains_position((0, 0)));
+}
+
+#[test]
+fn test_to_inline_comment() {
+    let sm =
+        SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#.to_vec()).unwrap();
+    let modern = sm.to_inline_comment(CommentStyle::default()).unwrap();
+    let legacy = sm.to_inline_comment(CommentStyle::Legacy).unwrap();
+    let data = "data:application/json;charset=utf-8;base64,eyJ2ZXJzaW9uIjozLCJzb3VyY2VzIjpbImEuanMiXSwic291cmNlc0NvbnRlbnQiOltudWxsXSwibWFwcGluZ3MiOiJBQUFBIn0=";
+    assert_eq!(modern, format!("//# sourceMappingURL={data}"));
+    assert_eq!(legacy, format!("//@ sourceMappingURL={data}"));
+}
+
+#[test]
+fn test_classify_position() {
+    // a generated-only segment at 0:4, and an empty last line with the end sentinel
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["a.js"],"mappings":"CAAA,G,CAAC;AACA;"}"#.to_vec(),
+    )
+    .unwrap();
+    assert_eq!(sm.classify_position((0, 0)), PositionClass::BeforeStart);
+    assert_eq!(
+        sm.classify_position((0, 3)),
+        PositionClass::Mapped(Mapping::new(0, 1).with_source(0, 0, 0))
+    );
+    assert_eq!(sm.classify_position((0, 4)), PositionClass::UnmappedGap);
+    assert_eq!(
+        sm.classify_position((1, 9)),
+        PositionClass::Mapped(Mapping::new(1, 0).with_source(0, 1, 1))
+    );
+    assert_eq!(sm.classify_position((2, 0)), PositionClass::PastEnd);
+    assert_eq!(sm.classify_position((9, 9)), PositionClass::PastEnd);
+
+    // without the end sentinel, the lines after the last mapped one are past the end
+    let mut options = ParseOptions::default();
+    options.insert_end_sentinel = false;
+    let sm = BorrowedSourceMap::from_slice_with_options(
+        &mut br#"{"version":3,"sources":["a.js"],"mappings":"AAAA"}"#.to_vec(),
+        &options,
+    )
+    .unwrap()
+    .into_owned();
+    assert!(matches!(
+        sm.classify_position((0, 9)),
+        PositionClass::Mapped(_)
+    ));
+    assert_eq!(sm.classify_position((1, 0)), PositionClass::PastEnd);
+
+    // a trailing generated-only segment on a mapped line is not the end sentinel
+    let sm = SourceMap::from(br#"{"version":3,"sources":["a.js"],"mappings":"CAAA,C"}"#.to_vec())
+        .unwrap();
+    assert_eq!(sm.classify_position((0, 2)), PositionClass::UnmappedGap);
+    assert_eq!(sm.classify_position((0, 5)), PositionClass::UnmappedGap);
+    assert_eq!(sm.classify_position((1, 0)), PositionClass::PastEnd);
+
+    let sm = SourceMap::from(br#"{"version":3,"sources":[],"mappings":""}"#.to_vec()).unwrap();
+    assert_eq!(sm.classify_position((0, 0)), PositionClass::BeforeStart);
+}
+
+#[test]
+fn test_explain() {
+    let sm = SourceMap::from(
+        br#"{"version":3,"sources":["../project/sum.ts",null],"names":["total"],"mappings":"CAAAA,CAAEA,C,CCAA"}"#
+            .to_vec(),
+    )
+    .unwrap();
+    let explanations = [(0, 0), (0, 1), (0, 9), (0, 2), (0, 3), (0, 4)]
+        .into_iter()
+        .map(|pos| sm.explain(pos))
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!(explanations, @r###"
+    no mapping at 0:0
+    generated 0:1 maps to ../project/sum.ts:0:0 (name 'total')
+    generated 0:9 maps to <null>:0:2
+    generated 0:2 maps to ../project/sum.ts:0:2 (name 'total')
+    generated 0:3 has no original source
+    generated 0:4 maps to <null>:0:2
+    "###);
+}
+
+#[test]
+fn test_from_slice_filtered() {
+    let json = include_bytes!("../benches/data/antd.min.js.map");
+    let full = SourceMap::from(json.to_vec()).unwrap();
+
+    let mut buf = json.to_vec();
+    let filtered =
+        BorrowedSourceMap::from_slice_filtered(&mut buf, |pos| (100..=110).contains(&pos.line))
+            .unwrap();
+    assert!(!filtered.mappings().is_empty());
+    assert!(filtered.mappings().len() < full.mappings().len() / 10);
+    assert!(filtered
+        .mappings()
+        .iter()
+        .all(|m| (100..=110).contains(&m.generated().line)));
+
+    // the running state is kept through the discarded mappings
+    for mapping in full
+        .mappings()
+        .iter()
+        .filter(|m| (100..=110).contains(&m.generated().line))
+    {
+        let pos = mapping.generated();
+        assert_eq!(filtered.find_mapping(pos).as_ref(), Some(mapping));
+        let next = Position::new(pos.line, pos.column + 1);
+        assert_eq!(filtered.find_mapping(next), full.find_mapping(next));
+    }
+}
@@ -3,7 +3,7 @@
 
 use paste::paste;
 use serde::Deserialize;
-use sora::{ParseResult, Position, SourceMap};
+use sora::{ParseError, ParseResult, Position, SourceMap};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -86,9 +86,9 @@ test!(invalid_mapping_segment_with_negative_relative_source_index);
 ignore!(invalid_mapping_segment_with_negative_relative_original_line);
 ignore!(invalid_mapping_segment_with_negative_relative_original_column);
 test!(invalid_mapping_segment_with_negative_relative_name_index);
-// FIXME: reject pos > u32::MAX
-ignore!(invalid_mapping_segment_with_column_exceeding32_bits);
+test!(invalid_mapping_segment_with_column_exceeding32_bits);
 test!(invalid_mapping_segment_with_source_index_exceeding32_bits);
+// FIXME: reject source pos > u32::MAX
 ignore!(invalid_mapping_segment_with_original_line_exceeding32_bits);
 ignore!(invalid_mapping_segment_with_original_column_exceeding32_bits);
 test!(invalid_mapping_segment_with_name_index_exceeding32_bits);
@@ -118,6 +118,21 @@ test!(sources_non_null_sources_content_null);
 test!(transitive_mapping);
 test!(transitive_mapping_with_three_steps);
 
+#[test]
+fn test_spec_invalid_mapping_segment_field_count() {
+    for (name, expected) in [
+        ("invalid_mapping_segment_with_two_fields", 2),
+        ("invalid_mapping_segment_with_three_fields", 3),
+    ] {
+        let result = get_test_case(name).read_map();
+        assert!(
+            matches!(result, Err(ParseError::MappingSegmentFieldCount { found }) if found == expected),
+            "{}",
+            name
+        );
+    }
+}
+
 fn get_test_case(name: &str) -> &'static TestCase {
     let tests = TEST_CASES.get_or_init(|| {
         let description = fs::read("tests/source-map-tests/source-map-spec-tests.json").unwrap();